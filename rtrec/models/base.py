@@ -1,7 +1,11 @@
+import copy
 import logging
 
 from abc import ABC, abstractmethod
-from typing import Any, List, Optional, Tuple, Iterable, Self
+from typing import Any, Callable, List, Optional, Tuple, Iterable, Self
+
+import numpy as np
+from numpy.typing import ArrayLike
 
 from rtrec.utils.features import FeatureStore
 from rtrec.utils.identifiers import Identifier
@@ -17,11 +21,45 @@ class BaseModel(ABC):
         self.interactions = UserItemInteractions(**kwargs)
 
         # Initialize user and item ID mappings
-        self.user_ids = Identifier()
-        self.item_ids = Identifier()
+        # New non-integer users and items beyond max_users and max_items are rejected to bound memory.
+        # Both default to max_vocab.
+        max_vocab = kwargs.get("max_vocab", None)
+        max_users = kwargs.get("max_users", None)
+        max_items = kwargs.get("max_items", None)
+        self.user_ids = Identifier("user", max_vocab=max_vocab if max_users is None else max_users)
+        self.item_ids = Identifier("item", max_vocab=max_vocab if max_items is None else max_items)
 
         self.feature_store = FeatureStore()
 
+        # Mapping of event types to ratings used by fit_events
+        self.event_weights: dict[str, float] = kwargs.get("event_weights", {})
+
+        # Items never recommended regardless of their scores
+        self.blocked_item_ids: set[int] = set()
+
+        # Raise KeyError for unknown users and items at prediction time instead of falling back silently
+        self.strict: bool = kwargs.get("strict", False)
+
+    def snapshot(self, share_weights: bool = False) -> Self:
+        """
+        Create an independent copy of the model that is not affected by subsequent fits.
+        To hot-swap a model under serving traffic, fit a snapshot off the serving path and
+        rebind the reference used by readers to it (reference assignment is atomic in Python).
+        The interactions, identifiers and weights are deep-copied, which takes about as much time and memory as the model itself.
+        :param share_weights: Whether to share the learned weights with the model instead of copying them, e.g., for a read-only
+                              serving view. Only weights that fits replace rather than modify in place are shared.
+        :return: Deep copy of the model
+        """
+        memo = {id(weights): weights for weights in self._immutable_weights()} if share_weights else {}
+        return copy.deepcopy(self, memo)
+
+    def _immutable_weights(self) -> List[Any]:
+        """
+        List the learned weights which fits replace rather than modify in place, so that snapshots can share them.
+        :return: List of weight objects
+        """
+        return []
+
     def register_user_feature(self, user: Any, user_tags: List[str]) -> int:
         """
         Register user features in the feature store.
@@ -58,6 +96,47 @@ class BaseModel(ABC):
         """
         self.feature_store.clear_item_features(item_ids)
 
+    def block_item(self, item: Any) -> None:
+        """
+        Block an item from being recommended or returned as a similar item until it is unblocked.
+        :param item: Item to block
+        """
+        self.blocked_item_ids.add(self.item_ids.identify(item))
+
+    def unblock_item(self, item: Any) -> None:
+        """
+        Allow a blocked item to be recommended again.
+        :param item: Item to unblock
+        """
+        item_id = self.item_ids.get_id(item)
+        if item_id is not None:
+            self.blocked_item_ids.discard(item_id)
+
+    def _num_excluded(self) -> int:
+        """
+        Count the items never recommended, i.e., blocked items and items outside the current item namespace,
+        by which ranked lists are over-fetched.
+        """
+        return len(self.blocked_item_ids) + self.item_ids.num_outside_namespace()
+
+    def _is_excluded(self, item_id: int) -> bool:
+        """
+        Check whether an item is blocked or outside the current item namespace.
+        :param item_id: Item index
+        """
+        return item_id in self.blocked_item_ids or not self.item_ids.in_namespace(item_id)
+
+    def _drop_blocked(self, item_ids: List[int], top_k: int) -> List[int]:
+        """
+        Remove blocked items and items outside the current item namespace from a ranked list of item indices.
+        :param item_ids: Ranked list of item indices, which should include top_k + _num_excluded() items
+        :param top_k: Number of top items to keep
+        :return: List of up to top_k item indices without excluded items
+        """
+        if self._num_excluded() == 0:
+            return item_ids[:top_k]
+        return [item_id for item_id in item_ids if not self._is_excluded(item_id)][:top_k]
+
     def add_interactions(
             self,
             interactions: Iterable[Tuple[Any, Any, float, float]],
@@ -73,6 +152,9 @@ class BaseModel(ABC):
         """
         for user, item, tstamp, rating in interactions:
             try:
+                # Reject the interaction before identifying either, so that no user or item is left without interactions
+                self.user_ids.check_capacity(user)
+                self.item_ids.check_capacity(item)
                 user_id = self.user_ids.identify(user)
                 item_id = self.item_ids.identify(item)
                 self.interactions.add_interaction(user_id, item_id, tstamp, rating, upsert=update_interaction)
@@ -103,6 +185,62 @@ class BaseModel(ABC):
         self.add_interactions(interactions, update_interaction=update_interaction, record_interactions=True)
         return self._fit_recorded(progress_bar=progress_bar)
 
+    def fit_events(self, events: Iterable[Tuple[Any, Any, float, str]], update_interaction: bool=False, progress_bar: bool=True) -> Self:
+        """
+        Fit the recommender model on user-item events, rating each event by its type using event_weights.
+        Events of unknown types are skipped with a warning.
+        :param events: List of (user, item, tstamp, event_type) events
+        :param update_interaction: Whether to update existing interactions
+        :param progress_bar: Whether to display a progress bar
+        """
+        def iter_interactions() -> Iterable[Tuple[Any, Any, float, float]]:
+            for user, item, tstamp, event_type in events:
+                rating = self.event_weights.get(event_type)
+                if rating is None:
+                    logging.warning(f"Unknown event type: {event_type}")
+                    continue
+                yield user, item, tstamp, rating
+
+        return self.fit(iter_interactions(), update_interaction=update_interaction, progress_bar=progress_bar)
+
+    def fit_csr(self, indptr: ArrayLike, indices: ArrayLike, data: ArrayLike, timestamps: ArrayLike, update_interaction: bool=False, progress_bar: bool=True) -> Self:
+        """
+        Fit the recommender model on a user-item matrix given as CSR arrays (e.g., from scipy.sparse.csr_matrix),
+        where rows are user indices and columns are item indices used as pass-through integer IDs.
+        :param indptr: Row pointer array of shape (n_users + 1,)
+        :param indices: Column (item) index array of shape (nnz,)
+        :param data: Rating array of shape (nnz,)
+        :param timestamps: Timestamp array of shape (nnz,) aligned with data
+        :param update_interaction: Whether to update existing interactions
+        :param progress_bar: Whether to display a progress bar
+        """
+        indptr = np.asarray(indptr)
+        indices = np.asarray(indices)
+        data = np.asarray(data)
+        timestamps = np.asarray(timestamps)
+        if indptr.ndim != 1 or len(indptr) == 0:
+            raise ValueError("indptr must be a non-empty 1-dimensional array")
+        if not (len(indices) == len(data) == len(timestamps)):
+            raise ValueError(f"indices, data and timestamps must have the same length: {len(indices)}, {len(data)}, {len(timestamps)}")
+        if indptr[0] != 0 or indptr[-1] != len(indices):
+            raise ValueError(f"indptr must start with 0 and end with {len(indices)}: [{indptr[0]}, {indptr[-1]}]")
+        if np.any(np.diff(indptr) < 0):
+            raise ValueError("indptr must be monotonically non-decreasing")
+        if len(indices) > 0 and (not np.issubdtype(indices.dtype, np.integer) or indices.min() < 0):
+            raise ValueError("indices must be non-negative integers")
+        # Rows and columns are used as user and item indices as-is, which requires pass-through integer IDs
+        for identifier in (self.user_ids, self.item_ids):
+            if identifier.pass_through is False or identifier.namespace is not None:
+                raise ValueError(f"fit_csr requires pass-through integer {identifier.name} IDs without a namespace")
+        self.user_ids.pass_through = True
+        self.item_ids.pass_through = True
+
+        users = np.repeat(np.arange(len(indptr) - 1), np.diff(indptr))
+        for user_id, item_id, tstamp, rating in zip(users.tolist(), indices.tolist(), timestamps.astype(float).tolist(), data.astype(float).tolist()):
+            self.interactions.add_interaction(user_id, item_id, tstamp, rating, upsert=update_interaction)
+            self._record_interactions(user_id, item_id, tstamp, rating)
+        return self._fit_recorded(progress_bar=progress_bar)
+
     @abstractmethod
     def _fit_recorded(self, parallel: bool=False, progress_bar: bool=True) -> Self:
         """
@@ -121,7 +259,7 @@ class BaseModel(ABC):
         """
         raise NotImplementedError("bulk_fit method must be implemented in the derived class")
 
-    def recommend(self, user: Any, candidate_items: Optional[List[Any]] = None, user_tags: Optional[List[str]] = None, top_k: int = 10, filter_interacted: bool = True) -> List[Any]:
+    def recommend(self, user: Any, candidate_items: Optional[List[Any]] = None, user_tags: Optional[List[str]] = None, top_k: int = 10, filter_interacted: bool = True, min_popularity: Optional[int] = None) -> List[Any]:
         """
         Recommend top-K items for a given user.
         :param user: User to recommend items for
@@ -129,6 +267,7 @@ class BaseModel(ABC):
         :param user_tags: List of user tags
         :param top_k: Number of top items to recommend
         :param filter_interacted: Whether to filter out items the user has already interacted with
+        :param min_popularity: Minimum number of users who interacted with an item to recommend it. If None, no filtering is applied.
         :return: List of top-K items recommended for the user
         """
 
@@ -143,21 +282,62 @@ class BaseModel(ABC):
                 candidate_item_ids = None
 
         user_id = self.user_ids.get_id(user)
-        if self.user_ids.pass_through and user_id > self.interactions.max_user_id:
+        # Users identified without any stored interaction are outside the interaction matrix
+        if user_id is not None and user_id > self.interactions.max_user_id:
             user_id = None
+        if user_id is None and self.strict:
+            raise KeyError(f"Unknown user: {user!r}")
+
+        if min_popularity is not None:
+            item_counts = self.interactions.get_item_counts(candidate_item_ids)
+            # The candidate path does not filter interacted items, so drop them here
+            interacted_item_ids = set()
+            if filter_interacted and user_id is not None:
+                interacted_item_ids.update(self.interactions.get_user_items(user_id))
+            candidate_item_ids = [item_id for item_id, count in item_counts.items() if count >= min_popularity and item_id not in interacted_item_ids]
+            if len(candidate_item_ids) == 0:
+                return []
+
+        # Over-fetch to fill the blocked items' slots
+        num_blocked = self._num_excluded()
         if user_id is None:
-            hot_item_ids = self.interactions.get_hot_items(top_k, filter_interacted=False)
+            hot_item_ids = self.interactions.get_hot_items(top_k + num_blocked, filter_interacted=False)
             if candidate_item_ids is not None:
                 # take intersection between hot items and candidate items
                 hot_item_ids = [item_id for item_id in hot_item_ids if item_id in candidate_item_ids]
-            return hot_item_ids
+            return self._drop_blocked(hot_item_ids, top_k)
 
         # Get top-K recommendations
-        recommended_item_ids = self._recommend(user_id, candidate_item_ids=candidate_item_ids, user_tags=user_tags, top_k=top_k, filter_interacted=filter_interacted)
+        recommended_item_ids = self._recommend(user_id, candidate_item_ids=candidate_item_ids, user_tags=user_tags, top_k=top_k + num_blocked, filter_interacted=filter_interacted)
+        recommended_item_ids = self._drop_blocked(recommended_item_ids, top_k)
 
         # Resolve item indices to original item values
         return [self.item_ids.get(item_id) for item_id in recommended_item_ids]
 
+    def recommend_filtered(self, user: Any, predicate: Callable[[Any], bool], user_tags: Optional[List[str]] = None, top_k: int = 10, filter_interacted: bool = True) -> List[Any]:
+        """
+        Recommend top-K items for a given user among the items satisfying a predicate.
+        :param user: User to recommend items for
+        :param predicate: Function applied to each candidate item, keeping only the items for which it returns True.
+                          It is not called for blocked items and items outside the current item namespace.
+        :param user_tags: List of user tags
+        :param top_k: Number of top items to recommend
+        :param filter_interacted: Whether to filter out items the user has already interacted with
+        :return: List of top-K items satisfying the predicate recommended for the user
+        """
+        interacted_item_ids = set()
+        if filter_interacted:
+            user_id = self.user_ids.get_id(user)
+            if user_id is not None:
+                interacted_item_ids.update(self.interactions.get_user_items(user_id))
+        candidate_items = [
+            item for item_id in self.interactions.get_all_item_ids()
+            if item_id not in interacted_item_ids and not self._is_excluded(item_id) and predicate(item := self.item_ids.get(item_id))
+        ]
+        if len(candidate_items) == 0:
+            return []
+        return self.recommend(user, candidate_items=candidate_items, user_tags=user_tags, top_k=top_k, filter_interacted=filter_interacted)
+
     @abstractmethod
     def _recommend(self, user_id: int, candidate_item_ids: Optional[List[int]] = None, user_tags: Optional[List[str]] = None, top_k: int = 10, filter_interacted: bool = True) -> List[int]:
         """
@@ -184,10 +364,11 @@ class BaseModel(ABC):
         user_ids = []
         for user in users:
             uid = self.user_ids.get_id(user)
-            if self.user_ids.pass_through and uid > self.interactions.max_user_id:
-                user_ids.append(None)
-            else:
-                user_ids.append(uid)
+            if uid is not None and uid > self.interactions.max_user_id:
+                uid = None
+            if uid is None and self.strict:
+                raise KeyError(f"Unknown user: {user!r}")
+            user_ids.append(uid)
         candidate_item_ids = None
         if candidate_items is not None:
             candidate_item_ids = [
@@ -198,8 +379,8 @@ class BaseModel(ABC):
             if len(candidate_item_ids) == 0:
                 candidate_item_ids = None
 
-        results = self._recommend_batch(user_ids, candidate_item_ids=candidate_item_ids, users_tags=users_tags, top_k=top_k, filter_interacted=filter_interacted)
-        return [[self.item_ids.get(item_id) for item_id in internal_ids] for internal_ids in results]
+        results = self._recommend_batch(user_ids, candidate_item_ids=candidate_item_ids, users_tags=users_tags, top_k=top_k + self._num_excluded(), filter_interacted=filter_interacted)
+        return [[self.item_ids.get(item_id) for item_id in self._drop_blocked(internal_ids, top_k)] for internal_ids in results]
 
     def _recommend_batch(self, user_ids: List[int], candidate_item_ids: Optional[List[int]] = None, users_tags: Optional[List[List[str]]] = None, top_k: int = 10, filter_interacted: bool = True) -> List[List[int]]:
         """
@@ -243,21 +424,105 @@ class BaseModel(ABC):
                     results.append(recommended_item_ids)
         return results
 
-    def similar_items(self, query_item: Any, query_item_tags: Optional[List[str]] = None, top_k: int = 10, ret_scores: bool=False) -> List[Tuple[Any, float]] | List[Any]:
+    def item_users(self, item: Any, limit: Optional[int] = None) -> List[Tuple[Any, float]]:
+        """
+        Find the users who interacted with a given item.
+        :param item: Item to find users for
+        :param limit: Maximum number of users to return. If None, return all users.
+        :return: List of (user, rating) pairs sorted by rating in descending order
+        """
+        item_id = self.item_ids.get_id(item)
+        if item_id is None:
+            return []
+        item_users = self.interactions.get_item_users(item_id)
+        if self.user_ids.num_outside_namespace() > 0:
+            item_users = [(user_id, rating) for user_id, rating in item_users if self.user_ids.in_namespace(user_id)]
+        if limit is not None:
+            item_users = item_users[:limit]
+        return [(self.user_ids.get(user_id), rating) for user_id, rating in item_users]
+
+    def trending_items(self, top_k: int = 10, half_life_days: Optional[float] = None) -> List[Tuple[Any, float]]:
+        """
+        Find the items popular recently, counting each user's positive interaction decayed by its age.
+        :param top_k: Number of top items to return
+        :param half_life_days: Half-life of an interaction in days. If None, the model's decay setting is used.
+        :return: List of (item, decayed popularity) pairs sorted by popularity in descending order, without blocked items
+                 and items outside the current item namespace
+        """
+        trending_items = self.interactions.get_trending_items(top_k + self._num_excluded(), half_life_days=half_life_days)
+        trending_items = [(item_id, popularity) for item_id, popularity in trending_items if not self._is_excluded(item_id)][:top_k]
+        return [(self.item_ids.get(item_id), popularity) for item_id, popularity in trending_items]
+
+    def similar_users(self, query_users: List[Any], top_k: int = 10) -> List[List[Tuple[Any, float]]]:
+        """
+        Find similar users by cosine similarity of their interaction vectors, independent of the model.
+        :param query_users: Users to find similar users for
+        :param top_k: Number of similar users to return for each query user
+        :return: List of (user, similarity) pairs sorted by similarity in descending order for each query user.
+                 The list is empty for an unknown query user.
+        """
+        results: List[List[Tuple[Any, float]]] = [[] for _ in query_users]
+        query_user_ids = [(i, user_id) for i, user in enumerate(query_users)
+                          if (user_id := self.user_ids.get_id(user)) is not None and user_id <= self.interactions.max_user_id]
+        if len(query_user_ids) == 0:
+            return results
+
+        matrix = self.interactions.to_csr()
+        norms = np.sqrt(np.asarray(matrix.multiply(matrix).sum(axis=1)).ravel())
+        # don't divide by zero for users without any non-zero rating
+        norms[norms == 0] = 1e-10
+        query_matrix = matrix[[user_id for _, user_id in query_user_ids], :]
+        similarities = np.asarray((query_matrix @ matrix.T).todense()) / np.outer(norms[[user_id for _, user_id in query_user_ids]], norms)
+
+        for row, (i, user_id) in enumerate(query_user_ids):
+            user_similarities = similarities[row]
+            user_similarities[user_id] = 0.0
+            candidate_ids = np.flatnonzero(user_similarities > 0.0)
+            if self.user_ids.num_outside_namespace() > 0:
+                candidate_ids = np.array([candidate_id for candidate_id in candidate_ids.tolist() if self.user_ids.in_namespace(candidate_id)], dtype=np.int64)
+            candidate_ids = candidate_ids[np.argsort(-user_similarities[candidate_ids], kind="stable")][:top_k]
+            results[i] = [(self.user_ids.get(candidate_id), float(user_similarities[candidate_id])) for candidate_id in candidate_ids.tolist()]
+        return results
+
+    def export_interactions_csr(self) -> Tuple[np.ndarray, np.ndarray, np.ndarray, List[Any], List[Any]]:
+        """
+        Export the user-item interaction matrix the model is trained on in CSR format.
+        :return: Tuple of (indptr, indices, data, users, items) where row i of the matrix is users[i]
+                 and column j is items[j]. Ratings are decayed as used for training.
+                 Without any interaction, the matrix is empty with indptr [0] and no users or items.
+        """
+        if len(self.interactions.get_all_users()) == 0:
+            empty = np.array([], dtype=np.int32)
+            return np.zeros(1, dtype=np.int32), empty, np.array([], dtype=np.float32), [], []
+        matrix = self.interactions.to_csr()
+        matrix.sort_indices()
+        users = [self.user_ids.get(user_id) for user_id in range(matrix.shape[0])]
+        items = [self.item_ids.get(item_id) for item_id in range(matrix.shape[1])]
+        return matrix.indptr.astype(np.int32), matrix.indices.astype(np.int32), matrix.data.astype(np.float32), users, items
+
+    def similar_items(self, query_item: Any, query_item_tags: Optional[List[str]] = None, top_k: int = 10, ret_scores: bool=False, **kwargs: Any) -> List[Tuple[Any, float]] | List[Any]:
         """
         Find similar items for a list of query items.
         :param query_item: List of query items
         :param query_item_tags: List of query item tags
         :param top_k: Number of top similar items to return for each query item
         :param ret_scores: Whether to return similarity scores. Defaults to False.
+        :param kwargs: Model-specific options passed to _similar_items
         :return: List of top-K similar items for each query item with similarity scores. If ret_scores is False, only return similar items.
         """
-        query_item_id = self.item_ids.identify(query_item)
+        query_item_id = self.item_ids.get_id(query_item)
+        if query_item_id is not None and query_item_id > self.interactions.max_item_id:
+            query_item_id = None
         if query_item_id is None:
+            if self.strict:
+                raise KeyError(f"Unknown item: {query_item!r}")
             return []
 
         # Get top-K similar items
-        similar_item_ids = self._similar_items(query_item_id, query_item_tags=query_item_tags, top_k=top_k)
+        num_excluded = self._num_excluded()
+        similar_item_ids = self._similar_items(query_item_id, query_item_tags=query_item_tags, top_k=top_k + num_excluded, **kwargs)
+        if num_excluded > 0:
+            similar_item_ids = [(item_id, score) for item_id, score in similar_item_ids if not self._is_excluded(item_id)][:top_k]
 
         # Resolve item indices to original item values
         if ret_scores:
@@ -266,7 +531,7 @@ class BaseModel(ABC):
             return [self.item_ids.get(item_id) for item_id, _ in similar_item_ids]
 
     @abstractmethod
-    def _similar_items(self, query_item_id: int,  query_item_tags: Optional[List[str]] = None, top_k: int = 10) -> List[Tuple[int, float]]:
+    def _similar_items(self, query_item_id: int,  query_item_tags: Optional[List[str]] = None, top_k: int = 10, **kwargs: Any) -> List[Tuple[int, float]]:
         """
         Find similar items for a list of query items.
         :param query_item_id: item id to find similar items for
@@ -5,6 +5,7 @@ import numpy as np
 from numpy import ndarray
 from numpy.typing import ArrayLike
 import scipy.sparse as sp
+import scipy.sparse.linalg
 from sklearn.linear_model import ElasticNet
 import warnings
 from sklearn.exceptions import ConvergenceWarning
@@ -158,6 +159,11 @@ class SLIMElastic:
             alpha (float): Regularization strength.
             l1_ratio (float): The ratio between L1 and L2 regularization.
             positive_only (bool): Whether to enforce positive coefficients.
+            min_item_interactions (int): Minimum number of interactions for an item to be fitted as a target.
+            shard (Optional[Tuple[int, int]]): (shard index, number of shards) to only fit the target items j
+                where j % number of shards == shard index. As the shards learn disjoint columns of the item
+                similarity matrix, the full matrix is the sum of the matrices of all shards, given that the
+                shards are trained on the same interactions with the same item identifiers.
         """
         # self.eta0 = config.get("eta0", 0.001) # Learning rate used only for SGD
         self.alpha = config.get("alpha", 0.1) # Regularization strength
@@ -167,6 +173,12 @@ class SLIMElastic:
         self.tol = config.get("tol", 1e-4)
         self.random_state = config.get("random_state", 43)
         self.nn_feature_selection = config.get("nn_feature_selection", None)
+        # Items with fewer interactions than this are not fitted until they gain enough support
+        self.min_item_interactions = config.get("min_item_interactions", 0)
+        self.shard: Optional[Tuple[int, int]] = config.get("shard", None)
+        if self.shard is not None:
+            shard_index, num_shards = self.shard
+            assert 0 <= shard_index < num_shards, f"Invalid shard: {self.shard}"
 
         # Initialize an empty item similarity matrix (will be computed during fit) of type scipy.sparse.csc_matrix
         self.item_similarity = None
@@ -219,7 +231,8 @@ class SLIMElastic:
             warnings.simplefilter("ignore", category=ConvergenceWarning)
 
             # Iterate through each item (column) and fit the model
-            for j in tqdm(range(num_items), desc="Fitting SLIMElastic") if progress_bar else range(num_items):
+            target_items = self._filter_target_items(interaction_matrix, range(num_items))
+            for j in tqdm(target_items, desc="Fitting SLIMElastic") if progress_bar else target_items:
                 # Target column (current item)
                 y = X.get_col(j)
 
@@ -242,7 +255,7 @@ class SLIMElastic:
         return self
 
     def fit_in_parallel(
-        self, interaction_matrix: sp.csc_matrix, item_ids: Optional[ndarray] = None, progress_bar: bool = False, chunk_size: int = 100, num_workers: Optional[int] = None
+        self, interaction_matrix: sp.csc_matrix, item_ids: Optional[ndarray] = None, progress_bar: bool = False, chunk_size: int = 100, num_workers: Optional[int] = None, freeze_structure: bool = False
     ) -> Self:
         """
         Fit the SLIM ElasticNet model in parallel.
@@ -253,6 +266,7 @@ class SLIMElastic:
             progress_bar (bool): Whether to display a progress bar.
             chunk_size (int): Number of items per chunk for parallel processing.
             num_workers (int): Number of worker processes to use. Defaults to 70% of available CPU cores.
+            freeze_structure (bool): Whether to only update item pairs that already have a similarity.
 
         Returns:
             Self: The fitted SLIM ElasticNet model.
@@ -288,6 +302,7 @@ class SLIMElastic:
 
         if item_ids is None:
             item_ids = np.arange(num_items)
+        item_ids = np.array(self._filter_target_items(interaction_matrix, item_ids), dtype=np.int64)
 
         # Ignore convergence warnings
         with warnings.catch_warnings():
@@ -328,6 +343,8 @@ class SLIMElastic:
                 for result in results:
                     for j, (indices, values) in result.items():
                         for i, value in zip(indices, values):
+                            if freeze_structure and item_similarity[i, j] == 0:
+                                continue # do not introduce new item pairs
                             item_similarity[i, j] = value
 
         # Cleanup shared memory
@@ -342,6 +359,41 @@ class SLIMElastic:
         self.item_similarity = item_similarity.tocsc(copy=False)
         return self
 
+    def _filter_target_items(self, interaction_matrix: sp.csc_matrix | sp.csr_matrix, item_ids: List[int]) -> List[int]:
+        """
+        Filter the items to fit as targets by the shard and by the number of interactions.
+
+        Args:
+            interaction_matrix (csc_matrix | csr_matrix): User-item interaction matrix (sparse).
+            item_ids (List[int]): List of item indices to filter.
+
+        Returns:
+            List[int]: List of item indices to fit.
+        """
+        if self.shard is not None:
+            shard_index, num_shards = self.shard
+            item_ids = [j for j in item_ids if j % num_shards == shard_index]
+        return self._filter_by_support(interaction_matrix, item_ids)
+
+    def _filter_by_support(self, interaction_matrix: sp.csc_matrix | sp.csr_matrix, item_ids: List[int]) -> List[int]:
+        """
+        Filter out items with fewer interactions than min_item_interactions.
+
+        Args:
+            interaction_matrix (csc_matrix | csr_matrix): User-item interaction matrix (sparse).
+            item_ids (List[int]): List of item indices to filter.
+
+        Returns:
+            List[int]: List of item indices with enough interactions.
+        """
+        if self.min_item_interactions <= 1:
+            return list(item_ids)
+        if isinstance(interaction_matrix, sp.csc_matrix):
+            support = np.diff(interaction_matrix.indptr)
+        else:
+            support = np.bincount(interaction_matrix.indices, minlength=interaction_matrix.shape[1])
+        return [j for j in item_ids if support[j] >= self.min_item_interactions]
+
     @staticmethod
     def _fit_items(
         item_ids: ndarray,
@@ -440,7 +492,7 @@ class SLIMElastic:
             user_items.update(interaction_matrix[user_id, :].indices.tolist())
         return self.partial_fit_items(interaction_matrix, list(user_items), progress_bar)
 
-    def partial_fit_items(self, interaction_matrix: sp.csc_matrix | sp.csr_matrix, updated_items: List[int], parallel: bool=False, progress_bar: bool=False) -> Self:
+    def partial_fit_items(self, interaction_matrix: sp.csc_matrix | sp.csr_matrix, updated_items: List[int], parallel: bool=False, progress_bar: bool=False, freeze_structure: bool=False) -> Self:
         """
         Incrementally fit the SLIMElastic model with new or updated items.
 
@@ -449,10 +501,12 @@ class SLIMElastic:
             updated_items (list): List of item indices that were updated.
             parallel (bool): Whether to use parallel processing for fitting.
             progress_bar (bool): Whether to show a progress bar during training.
+            freeze_structure (bool): Whether to only update item pairs that already have a similarity,
+                                     which bounds the size of the item similarity matrix during incremental updates.
         """
         if isinstance(interaction_matrix, sp.csc_matrix):
             if parallel:
-                return self.fit_in_parallel(interaction_matrix, item_ids=np.array(updated_items), progress_bar=progress_bar)
+                return self.fit_in_parallel(interaction_matrix, item_ids=np.array(updated_items), progress_bar=progress_bar, freeze_structure=freeze_structure)
             X = CSCMatrixWrapper(interaction_matrix)
         elif isinstance(interaction_matrix, sp.csr_matrix):
             X = CSRMatrixWrapper(interaction_matrix)
@@ -473,7 +527,8 @@ class SLIMElastic:
         with warnings.catch_warnings():
             warnings.simplefilter("ignore", category=ConvergenceWarning)
 
-            for j in tqdm(updated_items, desc="Fitting SLIMElastic") if progress_bar else updated_items:
+            target_items = self._filter_target_items(interaction_matrix, updated_items)
+            for j in tqdm(target_items, desc="Fitting SLIMElastic") if progress_bar else target_items:
                 # Target column (current item)
                 y = X.get_col(j)
 
@@ -486,6 +541,8 @@ class SLIMElastic:
                 # Update the item similarity matrix with new coefficients (weights for each user-item interaction)
                 # self.item_similarity[:, j] = model.coef_
                 for i, value in zip(model.sparse_coef_.indices, model.sparse_coef_.data):
+                    if freeze_structure and item_similarity[i, j] == 0:
+                        continue # do not introduce new item pairs
                     item_similarity[i, j] = value
 
                 # Reattach the item column after training
@@ -654,6 +711,124 @@ class SLIMElastic:
         # Extract and return the top-k indices
         return [idx for idx, _ in top_items]
 
+    def memory_usage(self) -> int:
+        """
+        Compute the memory used by the item similarity matrix in bytes.
+
+        Returns:
+            int: Number of bytes used by the arrays of the item similarity matrix.
+        """
+        if self.item_similarity is None:
+            return 0
+        return self.item_similarity.data.nbytes + self.item_similarity.indices.nbytes + self.item_similarity.indptr.nbytes
+
+    def prune_to_size(self, max_weights: int) -> int:
+        """
+        Keep at most max_weights entries of the item similarity matrix with the largest absolute weights
+        and drop the rest, bounding the memory used for serving.
+
+        Args:
+            max_weights (int): Maximum number of weights to keep.
+
+        Returns:
+            int: Number of weights removed.
+        """
+        assert max_weights >= 0, f"max_weights must be a non-negative integer: {max_weights}"
+        if self.item_similarity is None or self.item_similarity.nnz <= max_weights:
+            return 0
+
+        item_similarity = self.item_similarity.copy()
+        num_weights = item_similarity.nnz
+        keep = np.zeros(num_weights, dtype=bool)
+        if max_weights > 0:
+            keep[np.argpartition(-np.abs(item_similarity.data), max_weights - 1)[:max_weights]] = True
+        item_similarity.data[~keep] = 0
+        item_similarity.eliminate_zeros()
+        self.item_similarity = item_similarity
+        return num_weights - item_similarity.nnz
+
+    def item_cosine_similarity(self, item_ids: List[int]) -> ndarray:
+        """
+        Compute pairwise cosine similarities between items using their columns in the item similarity matrix.
+
+        Args:
+            item_ids (List[int]): List of item indices.
+
+        Returns:
+            numpy.ndarray: Cosine similarity matrix of shape (len(item_ids), len(item_ids)).
+        """
+        if self.item_similarity is None:
+            raise RuntimeError("Model must be fitted before calling item_cosine_similarity.")
+
+        # Each row holds the learned weights from all items to the target item
+        vectors = self.item_similarity[:, item_ids].T.tocsr()
+        norms = np.sqrt(np.asarray(vectors.multiply(vectors).sum(axis=1)).ravel())
+        # don't divide by zero for items without any learned weight
+        norms[norms == 0] = 1e-10
+        dots = safe_sparse_dot(vectors, vectors.T, dense_output=True)
+        return np.asarray(dots) / np.outer(norms, norms)
+
+    def item_embeddings(self, dim: int) -> ndarray:
+        """
+        Compute dense item embeddings by truncated SVD of the item similarity matrix W ~= U S V^T.
+        The embedding of item i is row i of U scaled by the square root of the singular values.
+
+        The sign of each singular vector is fixed so that its largest absolute component is positive,
+        so the result is deterministic (see _truncated_svd).
+
+        Args:
+            dim (int): Embedding dimension, which must be less than the number of items.
+
+        Returns:
+            numpy.ndarray: Item embeddings of shape (n_items, dim), ordered by descending singular value.
+        """
+        if self.item_similarity is None:
+            raise RuntimeError("Model must be fitted before calling item_embeddings.")
+
+        u, s = self._truncated_svd(dim)
+        signs = np.sign(u[np.abs(u).argmax(axis=0), np.arange(dim)])
+        signs[signs == 0] = 1.0
+        return (u * signs * np.sqrt(s)).astype(np.float32)
+
+    def singular_values(self, k: int) -> ndarray:
+        """
+        Compute the top-k singular values of the item similarity matrix, e.g., to see how much of its energy
+        concentrates in a few dimensions. The values are computed by truncated SVD (see _truncated_svd).
+
+        Args:
+            k (int): Number of singular values, which must be less than the number of items.
+
+        Returns:
+            numpy.ndarray: Singular values of shape (k,) in descending order.
+        """
+        if self.item_similarity is None:
+            raise RuntimeError("Model must be fitted before calling singular_values.")
+
+        _, s = self._truncated_svd(k)
+        return s
+
+    def _truncated_svd(self, k: int) -> Tuple[ndarray, ndarray]:
+        """
+        Compute the top-k singular triplets of the item similarity matrix by ARPACK, which converges to the
+        exact values up to a tolerance of machine precision. ARPACK starts from a fixed vector instead of
+        a random one, so the result is deterministic.
+
+        Args:
+            k (int): Number of singular values, which must be less than the number of items.
+
+        Returns:
+            Tuple[numpy.ndarray, numpy.ndarray]: Left singular vectors of shape (n_items, k) and
+                singular values of shape (k,), ordered by descending singular value.
+        """
+        num_items = self.item_similarity.shape[0]
+        if not 0 < k < num_items:
+            raise ValueError(f"k must be in (0, {num_items}): {k}")
+
+        u, s, _ = sp.linalg.svds(self.item_similarity.astype(np.float64), k=k, v0=np.full(num_items, 1.0 / np.sqrt(num_items)))
+        # svds returns singular values in ascending order
+        order = np.argsort(-s, kind="stable")
+        return u[:, order], s[order]
+
     def similar_items(self, item_id: int, top_k: int=10) -> List[Tuple[int, float]]:
         """
         Get the top-K most similar items to a given item.
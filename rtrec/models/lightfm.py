@@ -26,6 +26,9 @@ class LightFM(BaseModel):
         item_id_set, user_id_set = set(), set()
         for user, item, tstamp, rating in interactions:
             try:
+                # Reject the interaction before identifying either, so that no user or item is left without interactions
+                self.user_ids.check_capacity(user)
+                self.item_ids.check_capacity(item)
                 user_id = self.user_ids.identify(user)
                 item_id = self.item_ids.identify(item)
                 self.interactions.add_interaction(user_id, item_id, tstamp, rating, upsert=update_interaction)
@@ -160,7 +163,9 @@ class LightFM(BaseModel):
             results.append(ids.tolist())
         return results
 
-    def _similar_items(self, query_item_id: int, query_item_tags: Optional[List[str]] = None, top_k: int = 10) -> List[Tuple[int, float]]:
+    def _similar_items(self, query_item_id: int, query_item_tags: Optional[List[str]] = None, top_k: int = 10, **kwargs: Any) -> List[Tuple[int, float]]:
+        if kwargs:
+            raise ValueError(f"Unsupported options for LightFM similar_items: {sorted(kwargs)}")
         items_tags = [query_item_tags] if query_item_tags is not None else None
         query_features = self._create_item_features(item_ids=[query_item_id], items_tags=items_tags, slice=True)
         target_features = self._create_item_features()
@@ -1,32 +1,279 @@
 import logging
-from typing import Any, Iterable, List, Optional, Tuple, override, Self
-from scipy.sparse import csc_matrix
+import math
+import time
+from dataclasses import asdict, dataclass, field
+from enum import Enum
+from typing import Any, Dict, Iterable, List, Optional, Tuple, override, Self
+from scipy.sparse import csc_matrix, csr_matrix, spmatrix
+import numpy as np
+import pandas as pd
 
 from ..models.internal.slim_elastic import SLIMElastic
+from ..utils.identifiers import Identifier
+from ..utils.interactions import RatingTransform, UserItemInteractions
+from ..utils.lru import LRUCache
+from ..utils.metrics import compute_scores, score_auc
 from .base import BaseModel
 
+# Impressions whose penalty has decayed below this fraction are forgotten
+MIN_IMPRESSION_PENALTY = 1e-3
+
+class MissingSimilarity(Enum):
+    """
+    How similar_items treats item pairs that have no learned similarity.
+    """
+    DROP = "drop" # Only return items with a learned similarity
+    ZERO = "zero" # Fill up with the remaining known items scored 0.0
+    COOCCURRENCE = "cooccurrence" # Fill up with items scored by their co-occurrence with the query item
+
+@dataclass
+class SLIMConfig:
+    """
+    Hyperparameters of SLIM with their defaults, equivalent to the keyword arguments of SLIM(**kwargs).
+    """
+    # User-item interactions
+    min_value: int = -5
+    max_value: int = 10
+    decay_in_days: Optional[int] = None
+    n_recent_hot: int = 100_000
+    rating_transform: Optional[RatingTransform] = None
+    # User and item identifiers
+    max_vocab: Optional[int] = None
+    max_users: Optional[int] = None
+    max_items: Optional[int] = None
+    event_weights: Dict[str, float] = field(default_factory=dict)
+    # Item similarity learning
+    alpha: float = 0.1
+    l1_ratio: float = 0.1
+    positive_only: bool = True
+    max_iter: int = 100
+    tol: float = 1e-4
+    random_state: int = 43
+    nn_feature_selection: Optional[int] = None
+    min_item_interactions: int = 0
+    shard: Optional[Tuple[int, int]] = None
+    # Recommendation
+    missing_similarity: MissingSimilarity = MissingSimilarity.DROP
+    min_profile_signal: Optional[float] = None
+    impression_decay: Optional[float] = None
+    strict: bool = False
+
 class SLIM(BaseModel):
 
     def __init__(self, **kwargs):
         super().__init__(**kwargs)
         self.model = SLIMElastic(kwargs)
         self.recorded_item_ids = set()
+        self.missing_similarity = MissingSimilarity(kwargs.get("missing_similarity", MissingSimilarity.DROP))
+        # Users whose total absolute rating is at most this threshold get popular items instead of personalized ones
+        self.min_profile_signal = kwargs.get("min_profile_signal", None)
+        # Cache of dense item scores per user, enabled by enable_score_cache
+        self.score_cache: Optional[LRUCache] = None
+        # Cache of final recommendations per user with expiry, enabled by enable_rec_cache
+        self.rec_cache: Optional[LRUCache] = None
+        self.rec_cache_ttl = 0.0
+        # Half-life in days of the penalty on items recently shown to a user. If None, impressions are not penalized.
+        self.impression_decay = kwargs.get("impression_decay", None)
+        # Latest impression time of each item per user, recorded by record_impressions
+        self.impressions: Dict[int, Dict[int, float]] = {}
+
+    @classmethod
+    def from_config(cls, config: SLIMConfig) -> Self:
+        """
+        Create a SLIM model from a configuration.
+        :param config: Hyperparameters of the model
+        :return: SLIM model
+        """
+        return cls(**asdict(config))
+
+    @classmethod
+    def from_parts(cls, interactions: UserItemInteractions, item_similarity: spmatrix, user_ids: Identifier, item_ids: Identifier, **kwargs) -> Self:
+        """
+        Assemble a SLIM model from existing components without fitting.
+        :param interactions: User-item interactions
+        :param item_similarity: Item similarity matrix of shape (n_items, n_items)
+        :param user_ids: User identifier vocabulary
+        :param item_ids: Item identifier vocabulary
+        :param kwargs: Additional keyword arguments for the model
+        :return: SLIM model composed of the given components
+        """
+        num_users, num_items = interactions.shape
+        if item_similarity.shape != (num_items, num_items):
+            raise ValueError(f"Item similarity matrix must be of shape {(num_items, num_items)}: {item_similarity.shape}")
+        if user_ids.pass_through is False and len(interactions.interactions) > 0 and len(user_ids.id_to_obj) < num_users:
+            raise ValueError(f"User IDs in interactions exceed the user vocabulary: {num_users} > {len(user_ids.id_to_obj)}")
+        if item_ids.pass_through is False and len(interactions.all_item_ids) > 0 and len(item_ids.id_to_obj) < num_items:
+            raise ValueError(f"Item IDs in interactions exceed the item vocabulary: {num_items} > {len(item_ids.id_to_obj)}")
+
+        model = cls(**kwargs)
+        model.interactions = interactions
+        model.user_ids = user_ids
+        model.item_ids = item_ids
+        model.model.item_similarity = csc_matrix(item_similarity)
+        return model
 
     @override
-    def fit(self, interactions: Iterable[Tuple[Any, Any, float, float]], update_interaction: bool=False, progress_bar: bool=True) -> None:
-        item_id_set = set()
+    def _immutable_weights(self) -> List[Any]:
+        # Fits, imports and pruning all assign a new item similarity matrix
+        return [] if self.model.item_similarity is None else [self.model.item_similarity]
+
+    def enable_score_cache(self, capacity: int) -> None:
+        """
+        Cache the scores of all items for up to capacity recently scored users, so that repeated
+        recommendations for the same user skip scoring. The cache is cleared whenever interactions
+        or item similarities change.
+        :param capacity: Maximum number of users to cache scores for
+        """
+        self.score_cache = LRUCache(capacity)
+
+    def _invalidate_score_cache(self) -> None:
+        if self.score_cache is not None:
+            self.score_cache.clear()
+
+    def enable_rec_cache(self, ttl_seconds: float, capacity: int) -> None:
+        """
+        Cache the top-K recommendations for up to capacity recently served users, returned as-is for
+        ttl_seconds for the same top_k, filter_interacted and item namespace. A user's entry is invalidated when
+        interactions of the user are added, and all entries when the item similarities are rebuilt
+        or replaced. Other users may be served stale recommendations after an incremental fit
+        until their entries expire.
+        :param ttl_seconds: Time to live of a cached entry in seconds
+        :param capacity: Maximum number of users to cache recommendations for
+        """
+        self.rec_cache = LRUCache(capacity)
+        self.rec_cache_ttl = ttl_seconds
+
+    def _invalidate_rec_cache(self, user_ids: Optional[Iterable[int]] = None) -> None:
+        if self.rec_cache is None:
+            return
+        if user_ids is None:
+            self.rec_cache.clear()
+            return
+        for user_id in user_ids:
+            self.rec_cache.invalidate(user_id)
+
+    @override
+    def block_item(self, item: Any) -> None:
+        super().block_item(item)
+        self._invalidate_rec_cache()
+
+    @override
+    def unblock_item(self, item: Any) -> None:
+        super().unblock_item(item)
+        self._invalidate_rec_cache()
+
+    @override
+    def recommend(self, user: Any, candidate_items: Optional[List[Any]] = None, user_tags: Optional[List[str]] = None, top_k: int = 10, filter_interacted: bool = True, min_popularity: Optional[int] = None) -> List[Any]:
+        user_id = self.user_ids.get_id(user)
+        if self.rec_cache is None or candidate_items is not None or user_tags is not None or min_popularity is not None or user_id is None:
+            return super().recommend(user, candidate_items=candidate_items, user_tags=user_tags, top_k=top_k, filter_interacted=filter_interacted, min_popularity=min_popularity)
+
+        entry = self.rec_cache.get(user_id)
+        if entry is not None:
+            cached_top_k, cached_filter_interacted, cached_namespace, expires_at, recommended = entry
+            if cached_top_k == top_k and cached_filter_interacted == filter_interacted and cached_namespace == self.item_ids.namespace and time.time() < expires_at:
+                return list(recommended)
+        recommended = super().recommend(user, top_k=top_k, filter_interacted=filter_interacted)
+        self.rec_cache.put(user_id, (top_k, filter_interacted, self.item_ids.namespace, time.time() + self.rec_cache_ttl, list(recommended)))
+        return recommended
+
+    @override
+    def add_interactions(self, interactions: Iterable[Tuple[Any, Any, float, float]], update_interaction: bool = False, record_interactions: bool = False) -> None:
+        if self.rec_cache is not None:
+            interactions = list(interactions)
+        super().add_interactions(interactions, update_interaction=update_interaction, record_interactions=record_interactions)
+        self._invalidate_score_cache()
+        if self.rec_cache is not None:
+            self._invalidate_rec_cache(user_id for user, _, _, _ in interactions if (user_id := self.user_ids.get_id(user)) is not None)
+
+    @override
+    def fit(self, interactions: Iterable[Tuple[Any, Any, float, float]], update_interaction: bool=False, progress_bar: bool=True, freeze_structure: bool=False, retain_interactions: bool=True) -> None:
+        """
+        Fit the recommender model on the given user-item interactions.
+        :param interactions: List of user-item interactions
+        :param update_interaction: Whether to update existing interactions
+        :param progress_bar: Whether to display a progress bar
+        :param freeze_structure: Whether to only update existing item similarities without introducing new item pairs
+        :param retain_interactions: Whether to store the interactions. If False, the item similarities of the given items
+                                    are learned from the given interactions alone and the interactions are discarded, which
+                                    bounds memory for serving similar_items only. The users are not registered, so recommend treats them as unknown.
+        """
+        item_id_set, user_id_set = set(), set()
+        # Batch-local row indices of the users when the interactions are not retained,
+        # so that the users are not registered without a profile to recommend from
+        batch_user_ids: Dict[Any, int] = {}
+        # Accumulate, clip, decay and transform the discarded interactions in the same way as the stored ones
+        batch_interactions = UserItemInteractions(min_value=self.interactions.min_value, max_value=self.interactions.max_value, rating_transform=self.interactions.rating_transform)
+        batch_interactions.set_decay_rate(self.interactions.get_decay_rate())
         for user, item, tstamp, rating in interactions:
             try:
-                user_id = self.user_ids.identify(user)
-                item_id = self.item_ids.identify(item)
-                self.interactions.add_interaction(user_id, item_id, tstamp, rating, upsert=update_interaction)
+                if retain_interactions:
+                    self.user_ids.check_capacity(user)
+                    self.item_ids.check_capacity(item)
+                    user_id = self.user_ids.identify(user)
+                    item_id = self.item_ids.identify(item)
+                    self.interactions.add_interaction(user_id, item_id, tstamp, rating, upsert=update_interaction)
+                    user_id_set.add(user_id)
+                else:
+                    item_id = self.item_ids.identify(item)
+                    batch_interactions.add_interaction(batch_user_ids.setdefault(user, len(batch_user_ids)), item_id, tstamp, rating, upsert=update_interaction)
                 item_id_set.add(item_id)
             except Exception as e:
                 logging.warning(f"Error processing interaction: {e}")
                 continue
         item_ids = list(item_id_set)
-        interaction_matrix = self.interactions.to_csc(item_ids)
-        self.model.partial_fit_items(interaction_matrix, item_ids, progress_bar=progress_bar)
+        if retain_interactions:
+            interaction_matrix = self.interactions.to_csc(item_ids)
+        else:
+            if len(item_ids) == 0:
+                return
+            num_items = max(self.interactions.shape[1], max(item_ids) + 1)
+            if self.model.item_similarity is not None:
+                num_items = max(num_items, self.model.item_similarity.shape[0])
+            # Keep the interaction matrix aligned with the item similarity matrix for scoring
+            self.interactions.max_item_id = num_items - 1
+            batch_interactions.max_item_id = num_items - 1
+            # Decay relative to the latest interaction known to the model
+            batch_interactions.max_timestamp = max(batch_interactions.max_timestamp, self.interactions.max_timestamp)
+            interaction_matrix = batch_interactions.to_csc()
+        self.model.partial_fit_items(interaction_matrix, item_ids, progress_bar=progress_bar, freeze_structure=freeze_structure)
+        self._invalidate_score_cache()
+        self._invalidate_rec_cache(user_id_set)
+
+    def fit_validated(self, interactions: Iterable[Tuple[Any, Any, float, float]], validation_fraction: float, top_k: int = 10, random_seed: Optional[int] = None, update_interaction: bool=False, progress_bar: bool=True) -> float:
+        """
+        Fit the recommender model on a random part of the given interactions and report recall@k on the rest.
+        The held-out interactions are neither fitted nor added to the user profiles used to score them.
+        :param interactions: List of user-item interactions
+        :param validation_fraction: Fraction of the interactions to hold out, between 0 and 1
+        :param top_k: Number of top items to recommend for recall@k
+        :param random_seed: The random seed for reproducibility
+        :param update_interaction: Whether to update existing interactions
+        :param progress_bar: Whether to display a progress bar
+        :return: Recall@k averaged across users with held-out interactions
+        """
+        assert 0 < validation_fraction < 1, f"validation_fraction must be between 0 and 1: {validation_fraction}"
+        interactions = list(interactions)
+        rng = np.random.default_rng(random_seed)
+        permutation = rng.permutation(len(interactions))
+        num_held_out = int(validation_fraction * len(interactions))
+        held_out = [interactions[i] for i in permutation[:num_held_out]]
+        self.fit([interactions[i] for i in permutation[num_held_out:]], update_interaction=update_interaction, progress_bar=progress_bar)
+
+        ground_truths: Dict[Any, List[Any]] = {}
+        for user, item, _, _ in held_out:
+            ground_truths.setdefault(user, []).append(item)
+
+        def generate_evaluation_pairs() -> Iterable[Tuple[List[Any], List[Any]]]:
+            for user, ground_truth in ground_truths.items():
+                if self.user_ids.get_id(user) is None:
+                    # All interactions of the user are held out, so there is no profile to recommend from
+                    yield [], ground_truth
+                    continue
+                recommended = [self.item_ids.get(item_id) for item_id in self._recommended_item_ids(user, top_k)]
+                yield recommended, ground_truth
+
+        return compute_scores(generate_evaluation_pairs(), top_k)["recall"]
 
     def _record_interactions(self, user_id: int, item_id: int, tstamp: float, rating: float) -> None:
         self.recorded_item_ids.add(item_id)
@@ -36,6 +283,8 @@ class SLIM(BaseModel):
         interaction_matrix = self.interactions.to_csc(item_ids)
         self.model.partial_fit_items(interaction_matrix, item_ids, parallel=parallel, progress_bar=progress_bar)
         self.recorded_item_ids.clear()
+        self._invalidate_score_cache()
+        self._invalidate_rec_cache()
 
     def bulk_fit(self, parallel: bool=False, progress_bar: bool=True) -> None:
         """
@@ -46,6 +295,42 @@ class SLIM(BaseModel):
         """
         interaction_matrix = self.interactions.to_csc()
         self.model.fit(interaction_matrix, parallel=parallel, progress_bar=progress_bar)
+        self._invalidate_score_cache()
+        self._invalidate_rec_cache()
+
+    def import_similarity_matrix(self, items: List[Any], indptr: List[int], indices: List[int], data: List[float]) -> None:
+        """
+        Replace the item similarities with an item-item matrix in CSR format computed elsewhere,
+        e.g., by implicit or scipy, where entry (i, j) is the weight of item i to recommend item j.
+        :param items: Items for the rows and columns of the matrix
+        :param indptr: Row pointer array of shape (len(items) + 1,)
+        :param indices: Column index array of shape (nnz,)
+        :param data: Weight array of shape (nnz,)
+        """
+        matrix = csr_matrix((data, indices, indptr), shape=(len(items), len(items)), dtype=np.float32).tocoo()
+        item_ids = np.array([self.item_ids.identify(item) for item in items], dtype=np.int64)
+        rows, cols = item_ids[matrix.row], item_ids[matrix.col]
+        # Drop self-loops as training never learns the weight of an item to itself
+        off_diagonal = rows != cols
+        num_self_loops = int(len(rows) - off_diagonal.sum())
+        if num_self_loops > 0:
+            logging.warning(f"Dropped {num_self_loops} self-loop entries from the imported similarity matrix")
+        num_items = max(self.interactions.shape[1], int(item_ids.max()) + 1 if len(item_ids) > 0 else 0)
+        # Keep the interaction matrix aligned with the item similarity matrix for scoring
+        self.interactions.max_item_id = num_items - 1
+        self.model.item_similarity = csc_matrix((matrix.data[off_diagonal], (rows[off_diagonal], cols[off_diagonal])), shape=(num_items, num_items), dtype=np.float32)
+        self._invalidate_score_cache()
+        self._invalidate_rec_cache()
+
+    def reset_weights(self) -> None:
+        """
+        Clear the learned item similarities while keeping the interactions and the user/item identifiers,
+        e.g., to retrain from scratch on the same data with bulk_fit.
+        """
+        self.model.item_similarity = None
+        self.recorded_item_ids.clear()
+        self._invalidate_score_cache()
+        self._invalidate_rec_cache()
 
     def _recommend(self, user_id: int, candidate_item_ids: Optional[List[int]] = None, user_tags: Optional[List[str]] = None, top_k: int = 10, filter_interacted: bool = True) -> List[int]:
         """
@@ -59,10 +344,134 @@ class SLIM(BaseModel):
         :return: List of top-K item indices recommended for the user
         """
         interaction_matrix = self.interactions.to_csr(select_users=[user_id])
+        if self.min_profile_signal is not None:
+            profile_signal = np.abs(interaction_matrix[user_id, :].data).sum()
+            if profile_signal <= self.min_profile_signal:
+                # Scores of a degenerate profile are all zero, so fall back to popular items
+                hot_item_ids = self.interactions.get_hot_items(top_k, user_id=user_id, filter_interacted=filter_interacted)
+                if candidate_item_ids is not None:
+                    hot_item_ids = [item_id for item_id in hot_item_ids if item_id in candidate_item_ids]
+                return hot_item_ids
         dense_output = not self.item_ids.pass_through
+        penalties = self._impression_penalties(user_id)
+        if candidate_item_ids is not None and len(penalties) > 0:
+            scores = np.asarray(self.model.predict_selected(user_id, candidate_item_ids, interaction_matrix)).ravel()
+            self._apply_impression_penalties(scores, penalties, candidate_item_ids)
+            return [candidate_item_ids[i] for i in np.argsort(-scores, kind="stable")[:top_k]]
+        if (self.score_cache is not None or len(penalties) > 0) and dense_output and candidate_item_ids is None:
+            scores = self._user_scores(user_id, interaction_matrix)
+            self._apply_impression_penalties(scores, penalties)
+            return self.model._dense_topk_indicies(scores, top_k, user_id, interaction_matrix, filter_interacted)
+        if len(penalties) > 0 and not dense_output and candidate_item_ids is None:
+            # Only the items with non-zero scores are ranked for pass-through item IDs
+            scores = csr_matrix(self.model.predict(user_id, interaction_matrix, dense_output=False))
+            self._apply_impression_penalties(scores.data, penalties, scores.indices.tolist())
+            return self.model._sparse_topk_indicies(scores, top_k, user_id, interaction_matrix, filter_interacted)
         return self.model.recommend(user_id, interaction_matrix, candidate_item_ids=candidate_item_ids, top_k=top_k, filter_interacted=filter_interacted, dense_output=dense_output)
 
-    def _similar_items(self, query_item_id: int, query_item_tags: Optional[List[str]] = None, top_k: int = 10) -> List[Tuple[int, float]]:
+    def record_impressions(self, user: Any, items: List[Any], tstamp: Optional[float] = None) -> None:
+        """
+        Record that items were shown to a user, so that recommend down-ranks them while the impressions are recent.
+        Impressions have no effect unless impression_decay is set. The impressions of the user decayed below
+        MIN_IMPRESSION_PENALTY are pruned here rather than when recommending.
+        :param user: User the items were shown to. Unknown users are ignored.
+        :param items: Items shown to the user. Unknown items are ignored.
+        :param tstamp: Time the items were shown. Defaults to the latest interaction time.
+        """
+        if tstamp is None:
+            tstamp = self.interactions.max_timestamp
+        user_id = self.user_ids.get_id(user)
+        if user_id is None or user_id > self.interactions.max_user_id:
+            return
+        user_impressions = self.impressions.setdefault(user_id, {})
+        for item in items:
+            item_id = self.item_ids.get_id(item)
+            if item_id is not None:
+                user_impressions[item_id] = max(user_impressions.get(item_id, tstamp), tstamp)
+        self._prune_impressions(user_id, self.interactions.max_timestamp)
+        self._invalidate_rec_cache([user_id])
+
+    def prune_impressions(self, now: Optional[float] = None) -> int:
+        """
+        Forget the impressions of all users whose penalty has decayed below MIN_IMPRESSION_PENALTY.
+        Impressions of a user are also pruned whenever the user is shown items.
+        :param now: Time to decay the impressions to. Defaults to the latest interaction time.
+        :return: Number of impressions removed
+        """
+        if now is None:
+            now = self.interactions.max_timestamp
+        num_impressions = sum(len(user_impressions) for user_impressions in self.impressions.values())
+        for user_id in list(self.impressions.keys()):
+            self._prune_impressions(user_id, now)
+        return num_impressions - sum(len(user_impressions) for user_impressions in self.impressions.values())
+
+    def _prune_impressions(self, user_id: int, now: float) -> None:
+        """
+        Remove the impressions of a user decayed below MIN_IMPRESSION_PENALTY.
+        :param user_id: User index
+        :param now: Time to decay the impressions to
+        """
+        if self.impression_decay is None or user_id not in self.impressions:
+            return
+        penalties = self._impression_penalties(user_id, now)
+        if len(penalties) == 0:
+            del self.impressions[user_id]
+        elif len(penalties) < len(self.impressions[user_id]):
+            self.impressions[user_id] = {item_id: self.impressions[user_id][item_id] for item_id in penalties}
+
+    def _impression_penalties(self, user_id: int, now: Optional[float] = None) -> Dict[int, float]:
+        """
+        Compute the penalty of the items recently shown to a user, which halves every impression_decay days
+        from 1.0 at the time of the impression. Impressions decayed below MIN_IMPRESSION_PENALTY are skipped.
+        :param user_id: User index
+        :param now: Time to decay the impressions to. Defaults to the latest interaction time, so that
+                    recommendations do not depend on the wall clock.
+        :return: Mapping of item indices to penalties in [MIN_IMPRESSION_PENALTY, 1]
+        """
+        if self.impression_decay is None or user_id not in self.impressions:
+            return {}
+        if now is None:
+            now = self.interactions.max_timestamp
+        penalties = {
+            item_id: 0.5 ** (max(now - tstamp, 0.0) / 86400.0 / self.impression_decay)
+            for item_id, tstamp in self.impressions[user_id].items()
+        }
+        return {item_id: penalty for item_id, penalty in penalties.items() if penalty >= MIN_IMPRESSION_PENALTY}
+
+    @staticmethod
+    def _apply_impression_penalties(scores: np.ndarray, penalties: Dict[int, float], item_ids: Optional[List[int]] = None) -> None:
+        """
+        Down-rank impressed items in place by reducing their scores by the penalty fraction of their magnitude.
+        :param scores: Array of scores of all items, or of item_ids if given
+        :param penalties: Mapping of item indices to penalties
+        :param item_ids: Item indices of the scores. If None, scores are indexed by item index.
+        """
+        if item_ids is None:
+            for item_id, penalty in penalties.items():
+                if item_id < len(scores):
+                    scores[item_id] -= penalty * abs(scores[item_id])
+            return
+        for i, item_id in enumerate(item_ids):
+            penalty = penalties.get(item_id)
+            if penalty is not None:
+                scores[i] -= penalty * abs(scores[i])
+
+    @override
+    def similar_items(self, query_item: Any, query_item_tags: Optional[List[str]] = None, top_k: int = 10, ret_scores: bool=False, blend: Optional[float] = None) -> List[Tuple[Any, float]] | List[Any]:
+        """
+        Find similar items for a query item.
+        :param query_item: Query item
+        :param query_item_tags: List of query item tags
+        :param top_k: Number of top similar items to return
+        :param ret_scores: Whether to return similarity scores. Defaults to False.
+        :param blend: Weight of the learned similarity when blending it with co-occurrence, in [0, 1]. If None, no blending.
+        :return: List of top-K similar items with similarity scores. If ret_scores is False, only return similar items.
+        """
+        if blend is not None:
+            assert 0.0 <= blend <= 1.0, f"blend must be in [0, 1]: {blend}"
+        return super().similar_items(query_item, query_item_tags=query_item_tags, top_k=top_k, ret_scores=ret_scores, blend=blend)
+
+    def _similar_items(self, query_item_id: int, query_item_tags: Optional[List[str]] = None, top_k: int = 10, blend: Optional[float] = None) -> List[Tuple[int, float]]:
         """
         Find similar items for a list of query items.
         :param query_item_ids: List of query item indices
@@ -71,4 +480,568 @@ class SLIM(BaseModel):
         :param filter_query_items: Whether to filter out items in the query_items list
         :return: List of top-K similar items for each query item with similarity scores
         """
-        return self.model.similar_items(query_item_id, top_k=top_k)
+        if blend is None:
+            similar_items = self.model.similar_items(query_item_id, top_k=top_k)
+        else:
+            similar_items = self._blended_similar_items(query_item_id, blend, top_k=top_k)
+        if self.missing_similarity is MissingSimilarity.DROP or (len(similar_items) >= top_k and similar_items[-1][1] >= 0.0):
+            return similar_items
+
+        # Fill up with items that have no learned similarity to the query item,
+        # ranked below the positively similar items and above the negatively similar ones
+        seen_item_ids = {item_id for item_id, _ in similar_items}
+        seen_item_ids.add(query_item_id)
+        if self.missing_similarity is MissingSimilarity.ZERO:
+            fill_items = [(item_id, 0.0) for item_id in sorted(self.interactions.get_all_item_ids())]
+        else:
+            co_occurrences = self.interactions.get_co_occurrences(query_item_id)
+            fill_items = sorted(co_occurrences.items(), key=lambda x: x[1], reverse=True)
+        non_negative_items = [(item_id, score) for item_id, score in similar_items if score >= 0.0]
+        negative_items = [(item_id, score) for item_id, score in similar_items if score < 0.0]
+        fill_items = [(item_id, score) for item_id, score in fill_items if item_id not in seen_item_ids]
+        return (non_negative_items + fill_items + negative_items)[:top_k]
+
+    def recommend_above(self, user: Any, min_score: float, filter_interacted: bool = True, max_items: Optional[int] = None) -> List[Tuple[Any, float]]:
+        """
+        Recommend all items scoring at least min_score for a given user, ranked by score in descending order.
+        :param user: User to recommend items for
+        :param min_score: Minimum score of the items to recommend
+        :param filter_interacted: Whether to filter out items the user has already interacted with
+        :param max_items: Maximum number of items to recommend. If None, no limit is applied.
+        :return: List of (item, score) pairs, which may be empty for a sparse user
+        """
+        user_id = self._known_user_id(user)
+        if user_id is None:
+            return []
+        scores = self._dense_scores(user_id, filter_interacted=filter_interacted)
+        item_ids = np.flatnonzero(scores >= min_score)
+        item_ids = item_ids[np.argsort(-scores[item_ids], kind="stable")]
+        if max_items is not None:
+            item_ids = item_ids[:max_items]
+        return [(self.item_ids.get(item_id), float(scores[item_id])) for item_id in item_ids.tolist()]
+
+    def score_stats(self, user: Any, filter_interacted: bool = True) -> Dict[str, float]:
+        """
+        Summarize the distribution of the scores of all candidate items for a given user, e.g., to tune min_score of recommend_above.
+        :param user: User to score items for
+        :param filter_interacted: Whether to exclude items the user has already interacted with
+        :return: Dictionary with min, max, mean, std, p25, p50, p75, p90 and p99 of the scores, or empty if there is nothing to score
+        """
+        user_id = self._known_user_id(user)
+        if user_id is None:
+            return {}
+        scores = self._dense_scores(user_id, filter_interacted=filter_interacted)
+        scores = scores[np.isfinite(scores)]
+        if len(scores) == 0:
+            return {}
+        stats = {
+            "min": float(scores.min()),
+            "max": float(scores.max()),
+            "mean": float(scores.mean()),
+            "std": float(scores.std()),
+        }
+        for percentile in (25, 50, 75, 90, 99):
+            stats[f"p{percentile}"] = float(np.percentile(scores, percentile))
+        return stats
+
+    def recommend_grouped(self, user: Any, per_source_k: int = 5, filter_interacted: bool = True) -> List[Tuple[Any, List[Tuple[Any, float]]]]:
+        """
+        Recommend items grouped by the user's items that contribute to them, e.g., for "because you interacted with X" rows.
+        Groups are ordered by the user's rating of the source item, and each recommended item appears in one group only.
+        :param user: User to recommend items for
+        :param per_source_k: Number of top items to recommend for each source item
+        :param filter_interacted: Whether to filter out items the user has already interacted with
+        :return: List of (source_item, [(item, similarity), ...]) pairs, omitting source items without recommendations
+        """
+        user_id = self._known_user_id(user)
+        if user_id is None or self.model.item_similarity is None:
+            return []
+        user_item_ids = self.interactions.get_user_items(user_id)
+        source_item_ids = sorted(user_item_ids, key=lambda item_id: self.interactions.get_user_item_rating(user_id, item_id), reverse=True)
+        seen_item_ids = set(user_item_ids) if filter_interacted else set()
+
+        item_similarity = self.model.item_similarity.tocsr()
+        groups = []
+        for source_item_id in source_item_ids:
+            if source_item_id >= item_similarity.shape[0]:
+                continue
+            # Weights from the source item to the target items it contributes to
+            start, end = item_similarity.indptr[source_item_id], item_similarity.indptr[source_item_id + 1]
+            targets = sorted(zip(item_similarity.indices[start:end].tolist(), item_similarity.data[start:end].tolist()), key=lambda x: x[1], reverse=True)
+            group = []
+            for item_id, weight in targets:
+                if len(group) >= per_source_k:
+                    break
+                if weight <= 0.0 or item_id == source_item_id or item_id in seen_item_ids or self._is_excluded(item_id):
+                    continue
+                group.append((self.item_ids.get(item_id), weight))
+                seen_item_ids.add(item_id)
+            if len(group) > 0:
+                groups.append((self.item_ids.get(source_item_id), group))
+        return groups
+
+    def recommend_with_reason(self, user: Any, top_k: int = 10, filter_interacted: bool = True) -> List[Tuple[Any, float, Optional[Any]]]:
+        """
+        Recommend top-K items for a given user along with the user's item contributing the most to each score,
+        e.g., for "because you interacted with X" explanations.
+        :param user: User to recommend items for
+        :param top_k: Number of top items to recommend
+        :param filter_interacted: Whether to filter out items the user has already interacted with
+        :return: List of (item, score, source_item) tuples ranked by score in descending order.
+                 source_item is None if no user's item contributes positively to the score.
+        """
+        user_id = self._known_user_id(user)
+        if user_id is None or self.model.item_similarity is None:
+            return []
+        scores = self._dense_scores(user_id, filter_interacted=filter_interacted)
+        item_ids = self._ranked_item_ids(scores, top_k)
+        if len(item_ids) == 0:
+            return []
+
+        user_row = self.interactions.to_csr(select_users=[user_id])[user_id, :]
+        in_range = user_row.indices < self.model.item_similarity.shape[0]
+        source_item_ids, ratings = user_row.indices[in_range], user_row.data[in_range]
+        # Contribution of each source item (row) to the score of each recommended item (column)
+        contributions = self.model.item_similarity[source_item_ids, :][:, item_ids].toarray() * ratings[:, np.newaxis]
+
+        results = []
+        for i, item_id in enumerate(item_ids.tolist()):
+            source_item = None
+            if len(source_item_ids) > 0:
+                best = int(np.argmax(contributions[:, i]))
+                if contributions[best, i] > 0.0:
+                    source_item = self.item_ids.get(int(source_item_ids[best]))
+            results.append((self.item_ids.get(item_id), float(scores[item_id]), source_item))
+        return results
+
+    def batch_explain(self, users: List[Any], item: Any) -> List[List[Tuple[Any, float]]]:
+        """
+        Explain the score of an item for each of the given users by the contributions of the users' items,
+        e.g., to audit why an item is recommended across a cohort.
+        :param users: Users to explain the score for
+        :param item: Item to explain the score of
+        :return: List of (source_item, contribution) pairs sorted by contribution in descending order for each user,
+                 omitting zero contributions. The list is empty for an unknown user, unless strict is set.
+        """
+        results: List[List[Tuple[Any, float]]] = [[] for _ in users]
+        item_id = self.item_ids.get_id(item)
+        if item_id is None or self.model.item_similarity is None or item_id >= self.model.item_similarity.shape[1]:
+            return results
+        user_ids = [(i, user_id) for i, user in enumerate(users) if (user_id := self._known_user_id(user)) is not None]
+        if len(user_ids) == 0:
+            return results
+
+        # Weights from all source items to the item
+        item_weights = self.model.item_similarity[:, item_id].toarray().ravel()
+        interaction_matrix = self.interactions.to_csr(select_users=[user_id for _, user_id in user_ids])
+        for i, user_id in user_ids:
+            user_row = interaction_matrix[user_id, :]
+            in_range = user_row.indices < len(item_weights)
+            source_item_ids = user_row.indices[in_range]
+            contributions = user_row.data[in_range] * item_weights[source_item_ids]
+            explanation = [(int(source_item_id), float(contribution)) for source_item_id, contribution in zip(source_item_ids, contributions) if contribution != 0.0]
+            explanation.sort(key=lambda x: x[1], reverse=True)
+            results[i] = [(self.item_ids.get(source_item_id), contribution) for source_item_id, contribution in explanation]
+        return results
+
+    def export_recommendations(self, path: str, top_k: int = 10, filter_interacted: bool = True, batch_size: int = 100) -> int:
+        """
+        Write the top-K recommendations of all users with interactions to a file with columns user, rank, item and score.
+        The file is written in Parquet if the path ends with .parquet, and in CSV otherwise. Remote paths such as
+        s3://bucket/key are supported through pandas.
+        :param path: Path of the file to write
+        :param top_k: Number of top items to recommend for each user
+        :param filter_interacted: Whether to filter out items the user has already interacted with
+        :param batch_size: Number of users to score at once. Each batch is scored into a dense array of
+                           batch_size x number of items in float64, e.g., 100 users x 1M items take 800MB.
+        :return: Number of rows written
+        """
+        rows = []
+        if self.model.item_similarity is not None:
+            user_ids = sorted(self.interactions.get_all_users())
+            interaction_matrix = self.interactions.to_csr()
+            excluded = None
+            for start in range(0, len(user_ids), batch_size):
+                batch_user_ids = user_ids[start:start + batch_size]
+                batch_matrix = interaction_matrix[batch_user_ids, :]
+                scores = np.array(self.model.predict_all(batch_matrix, dense_output=True), dtype=np.float64)
+                if excluded is None:
+                    excluded = self._excluded_items_mask(scores.shape[1])
+                scores[:, excluded] = -np.inf
+                if filter_interacted:
+                    batch_rows = np.repeat(np.arange(len(batch_user_ids)), np.diff(batch_matrix.indptr))
+                    scores[batch_rows, batch_matrix.indices] = -np.inf
+                for row, user_id in enumerate(batch_user_ids):
+                    user = self.user_ids.get(user_id)
+                    for rank, item_id in enumerate(self._ranked_item_ids(scores[row], top_k).tolist(), start=1):
+                        rows.append((user, rank, self.item_ids.get(item_id), float(scores[row, item_id])))
+
+        df = pd.DataFrame(rows, columns=["user", "rank", "item", "score"])
+        if path.endswith(".parquet"):
+            df.to_parquet(path, index=False)
+        else:
+            df.to_csv(path, index=False)
+        return len(df)
+
+    @staticmethod
+    def _ranked_item_ids(scores: np.ndarray, top_k: int) -> np.ndarray:
+        """
+        Rank the items with finite scores in descending order of score.
+        :param scores: Array of scores of shape (n_items,)
+        :param top_k: Number of top items to return
+        :return: Array of up to top_k item indices
+        """
+        item_ids = np.flatnonzero(np.isfinite(scores))
+        return item_ids[np.argsort(-scores[item_ids], kind="stable")][:top_k]
+
+    def _dense_scores(self, user_id: int, filter_interacted: bool = True, filter_blocked: bool = True) -> np.ndarray:
+        """
+        Compute the scores of all items for a given user. Unknown items, interacted items if filter_interacted is True,
+        and blocked items and items outside the current item namespace if filter_blocked is True are scored -inf.
+        :param user_id: User index
+        :param filter_interacted: Whether to filter out items the user has already interacted with
+        :param filter_blocked: Whether to filter out blocked items and items outside the current item namespace
+        :return: Array of scores of shape (n_items,)
+        """
+        interaction_matrix = self.interactions.to_csr(select_users=[user_id])
+        scores = self._user_scores(user_id, interaction_matrix)
+        scores[self._excluded_items_mask(len(scores), filter_blocked=filter_blocked)] = -np.inf
+        if filter_interacted:
+            scores[interaction_matrix[user_id, :].indices] = -np.inf
+        return scores
+
+    def _excluded_items_mask(self, num_items: int, filter_blocked: bool = True) -> np.ndarray:
+        """
+        Mark the items never recommended, i.e., unknown items, and blocked items and items outside
+        the current item namespace if filter_blocked is True.
+        :param num_items: Number of items to mark
+        :param filter_blocked: Whether to mark blocked items and items outside the current item namespace
+        :return: Boolean array of shape (num_items,) which is True for the excluded items
+        """
+        excluded = np.ones(num_items, dtype=bool)
+        excluded[[item_id for item_id in self.interactions.get_all_item_ids() if item_id < num_items]] = False
+        if filter_blocked and self._num_excluded() > 0:
+            excluded |= np.fromiter((self._is_excluded(item_id) for item_id in range(num_items)), dtype=bool, count=num_items)
+        return excluded
+
+    def _user_scores(self, user_id: int, interaction_matrix: csr_matrix) -> np.ndarray:
+        """
+        Compute the scores of all items for a given user, reusing the score cache if enabled.
+        :param user_id: User index
+        :param interaction_matrix: User-item interaction matrix including the user
+        :return: Array of scores of shape (n_items,), which the caller is free to modify
+        """
+        if self.score_cache is not None:
+            scores = self.score_cache.get(user_id)
+            if scores is not None:
+                return scores.copy()
+        scores = np.array(self.model.predict(user_id, interaction_matrix, dense_output=True), dtype=np.float64).ravel()
+        if self.score_cache is not None:
+            self.score_cache.put(user_id, scores.copy())
+        return scores
+
+    def _blended_similar_items(self, query_item_id: int, alpha: float, top_k: int = 10) -> List[Tuple[int, float]]:
+        """
+        Find similar items scored by alpha * learned similarity + (1 - alpha) * co-occurrence.
+        Items without a learned similarity are scored by co-occurrence alone.
+        :param query_item_id: item id to find similar items for
+        :param alpha: Weight of the learned similarity in [0, 1]
+        :param top_k: Number of top similar items to return
+        :return: List of top-K similar items with blended similarity scores
+        """
+        if self.model.item_similarity is None:
+            raise RuntimeError("Model must be fitted before calling similar_items.")
+        num_items = self.model.item_similarity.shape[0]
+        learned = dict(self.model.similar_items(query_item_id, top_k=num_items))
+        co_occurrences = self.interactions.get_co_occurrences(query_item_id)
+        scores = {
+            item_id: alpha * learned.get(item_id, 0.0) + (1.0 - alpha) * co_occurrences.get(item_id, 0.0)
+            for item_id in learned.keys() | co_occurrences.keys()
+        }
+        return sorted(scores.items(), key=lambda x: x[1], reverse=True)[:top_k]
+
+    def item_embeddings(self, dim: int) -> Tuple[List[Any], np.ndarray]:
+        """
+        Compute dense item embeddings of the learned item similarities by truncated SVD, e.g., for ANN retrieval.
+        The result is deterministic for the same item similarities.
+        :param dim: Embedding dimension, which must be less than the number of items
+        :return: Tuple of (items, embeddings) where embeddings[i] of shape (dim,) is the embedding of items[i]
+        """
+        embeddings = self.model.item_embeddings(dim)
+        items = [self.item_ids.get(item_id) for item_id in range(embeddings.shape[0])]
+        return items, embeddings
+
+    def matrix_spectrum(self, k: int) -> List[float]:
+        """
+        Compute the top-k singular values of the learned item similarities, e.g., to decide whether they can be
+        compressed into item_embeddings of a small dimension. The result is deterministic.
+        :param k: Number of singular values, which must be less than the number of items
+        :return: List of singular values in descending order
+        """
+        return self.model.singular_values(k).tolist()
+
+    def memory_usage(self) -> Dict[str, int]:
+        """
+        Estimate the memory used by each component of the model in bytes.
+        :return: Dictionary of estimated bytes for weights, interactions, user_ids and item_ids
+        """
+        return {
+            "weights": self.model.memory_usage(),
+            "interactions": self.interactions.memory_usage(),
+            "user_ids": self.user_ids.memory_usage(),
+            "item_ids": self.item_ids.memory_usage(),
+        }
+
+    def recommendable_coverage(self) -> float:
+        """
+        Compute the fraction of known items that can be recommended, i.e., have at least one learned similarity from another item.
+        :return: Fraction of recommendable items in [0, 1]
+        """
+        item_ids = self.interactions.get_all_item_ids()
+        if len(item_ids) == 0 or self.model.item_similarity is None:
+            return 0.0
+        # item_similarity is in CSC format, so the number of incoming weights per item is the column size
+        num_weights = np.diff(self.model.item_similarity.indptr)
+        num_covered = sum(1 for item_id in item_ids if item_id < len(num_weights) and num_weights[item_id] > 0)
+        return num_covered / len(item_ids)
+
+    def prune_to_size(self, max_weights: int) -> int:
+        """
+        Keep at most max_weights item similarities with the largest absolute weights and drop the rest.
+        :param max_weights: Maximum number of item similarities to keep
+        :return: Number of item similarities removed
+        """
+        num_removed = self.model.prune_to_size(max_weights)
+        self._invalidate_score_cache()
+        self._invalidate_rec_cache()
+        return num_removed
+
+    def validate(self) -> List[str]:
+        """
+        Check the invariants of the model, e.g., after assembling it from parts or importing item similarities.
+        :return: List of violations, which is empty if the model is valid
+        """
+        violations = []
+        num_users, num_items = self.interactions.shape
+        if not self.user_ids.pass_through and len(self.interactions.interactions) > 0 and num_users > len(self.user_ids.id_to_obj):
+            violations.append(f"User IDs in interactions exceed the user vocabulary: {num_users} > {len(self.user_ids.id_to_obj)}")
+        if not self.item_ids.pass_through and len(self.interactions.all_item_ids) > 0 and num_items > len(self.item_ids.id_to_obj):
+            violations.append(f"Item IDs in interactions exceed the item vocabulary: {num_items} > {len(self.item_ids.id_to_obj)}")
+
+        item_similarity = self.model.item_similarity
+        if item_similarity is None:
+            return violations
+        if item_similarity.shape[0] != item_similarity.shape[1]:
+            violations.append(f"Item similarity matrix is not square: {item_similarity.shape}")
+        if not self.item_ids.pass_through and item_similarity.shape[0] > len(self.item_ids.id_to_obj):
+            violations.append(f"Item similarity matrix exceeds the item vocabulary: {item_similarity.shape[0]} > {len(self.item_ids.id_to_obj)}")
+        num_non_finite = int(np.count_nonzero(~np.isfinite(item_similarity.data)))
+        if num_non_finite > 0:
+            violations.append(f"Item similarity matrix has {num_non_finite} non-finite weights")
+        num_self_loops = int(np.count_nonzero(item_similarity.diagonal()))
+        if num_self_loops > 0:
+            violations.append(f"Item similarity matrix has {num_self_loops} self-loop weights")
+        return violations
+
+    def recommendation_diversity(self, user: Any, top_k: int = 10) -> float:
+        """
+        Compute the intra-list diversity of the top-K recommendations for a given user.
+        Diversity is the mean pairwise cosine dissimilarity of the recommended items in the item similarity matrix.
+        :param user: User to recommend items for
+        :param top_k: Number of top items to recommend
+        :return: Intra-list diversity in [0, 2]. 0.0 if fewer than two items are recommended.
+        """
+        item_ids = self._recommended_item_ids(user, top_k)
+        if len(item_ids) < 2:
+            return 0.0
+        similarity = self.model.item_cosine_similarity(item_ids)
+        upper = np.triu_indices(len(item_ids), k=1)
+        return float(np.mean(1.0 - similarity[upper]))
+
+    def user_affinity(self, user_a: Any, user_b: Any) -> Dict[str, float]:
+        """
+        Compare two users by their interactions and by how well each user's profile predicts the other's items.
+        :param user_a: First user
+        :param user_b: Second user
+        :return: Dictionary with shared_items (number of items both users interacted with), jaccard (overlap of
+                 their items), a_to_b (mean score of user_b's items predicted from user_a's profile), b_to_a, and
+                 affinity (mean of a_to_b and b_to_a). Empty if either user is unknown, unless strict is set.
+        """
+        user_a_id, user_b_id = self._known_user_id(user_a), self._known_user_id(user_b)
+        if user_a_id is None or user_b_id is None:
+            return {}
+        items_a = set(self.interactions.get_user_items(user_a_id))
+        items_b = set(self.interactions.get_user_items(user_b_id))
+        num_shared = len(items_a & items_b)
+        num_union = len(items_a | items_b)
+
+        def mean_score(user_id: int, item_ids: set) -> float:
+            if self.model.item_similarity is None or len(item_ids) == 0:
+                return 0.0
+            scores = self._dense_scores(user_id, filter_interacted=False, filter_blocked=False)
+            return float(np.mean([scores[item_id] for item_id in item_ids if item_id < len(scores)] or [0.0]))
+
+        a_to_b = mean_score(user_a_id, items_b)
+        b_to_a = mean_score(user_b_id, items_a)
+        return {
+            "shared_items": float(num_shared),
+            "jaccard": num_shared / num_union if num_union > 0 else 0.0,
+            "a_to_b": a_to_b,
+            "b_to_a": b_to_a,
+            "affinity": (a_to_b + b_to_a) / 2.0,
+        }
+
+    def novelty(self, user: Any, top_k: int = 10) -> float:
+        """
+        Compute the novelty of the top-K recommendations for a given user.
+        Novelty is the mean of -log2(popularity) over the recommended items, where popularity
+        is the fraction of users who interacted with the item.
+        :param user: User to recommend items for
+        :param top_k: Number of top items to recommend
+        :return: Mean self-information of the recommended items. 0.0 if no item is recommended.
+        """
+        item_ids = self._recommended_item_ids(user, top_k)
+        num_users = len(self.interactions.get_all_users())
+        counts = [count for count in self.interactions.get_item_counts(item_ids).values() if count > 0]
+        if len(counts) == 0:
+            return 0.0
+        return sum(-math.log2(count / num_users) for count in counts) / len(counts)
+
+    def auc(self, positives: List[Tuple[Any, Any]], negatives: List[Tuple[Any, Any]]) -> float:
+        """
+        Compute ROC-AUC of the model scores over labeled (user, item) pairs, i.e.,
+        the probability that a random positive pair is scored higher than a random negative pair.
+        :param positives: List of positive (user, item) pairs
+        :param negatives: List of negative (user, item) pairs
+        :return: AUC score
+        """
+        return score_auc(self._predict_pairs(positives), self._predict_pairs(negatives))
+
+    def temporal_eval(self, cutoff: float, top_k: int = 10, filter_interacted: bool = True) -> Dict[str, float]:
+        """
+        Evaluate the model on a temporal split of the stored interactions. Each user's interactions before the cutoff
+        are used as the profile for scoring, and the items interacted at or after the cutoff are the ground truth.
+        Note the item similarities themselves are used as-is, so they should be fitted on interactions before the cutoff.
+        :param cutoff: Timestamp splitting the profile and the ground truth
+        :param top_k: Number of top items to recommend
+        :param filter_interacted: Whether to filter out the items in the profile
+        :return: Dictionary with averaged evaluation metrics across users with held-out interactions
+        """
+        profile_matrix, held_out = self.interactions.temporal_split(cutoff)
+
+        def generate_evaluation_pairs() -> Iterable[Tuple[List[int], List[int]]]:
+            for user_id, ground_truth in held_out.items():
+                if profile_matrix[user_id, :].nnz == 0:
+                    # Nothing to score without a profile before the cutoff
+                    yield [], ground_truth
+                    continue
+                scores = self.model.predict(user_id, profile_matrix, dense_output=True)
+                recommended = self.model._dense_topk_indicies(scores, top_k, user_id, profile_matrix, filter_interacted)
+                yield recommended, ground_truth
+
+        return compute_scores(generate_evaluation_pairs(), top_k)
+
+    def predict_with_weight_overrides(self, user: Any, item: Any, overrides: List[Tuple[Tuple[Any, Any], float]]) -> float:
+        """
+        Predict the score of a user-item pair with some item similarities substituted, without modifying the model.
+        :param user: User to predict the score for
+        :param item: Item to predict the score for
+        :param overrides: List of ((source_item, target_item), weight) pairs substituting the learned similarities
+        :return: Predicted score. 0.0 if the user or the item is unknown, unless strict is set.
+                 Items added after the last fit have no learned similarities and are scored by the overrides only.
+        """
+        if self.model.item_similarity is None:
+            raise RuntimeError("Model must be fitted before calling predict_with_weight_overrides.")
+        user_id = self.user_ids.get_id(user)
+        item_id = self.item_ids.get_id(item)
+        if self.strict:
+            self._check_known(user, user_id, item, item_id)
+        if user_id is None or item_id is None:
+            return 0.0
+        if user_id > self.interactions.max_user_id or item_id > self.interactions.max_item_id:
+            return 0.0
+
+        user_vector = self.interactions.to_csr(select_users=[user_id])[user_id, :].toarray().ravel()
+        # Align the learned weights with the user vector, which also covers the items added after the last fit
+        item_weights = np.zeros(len(user_vector))
+        if item_id < self.model.item_similarity.shape[1]:
+            learned_weights = self.model.item_similarity[:, item_id].toarray().ravel()[:len(user_vector)]
+            item_weights[:len(learned_weights)] = learned_weights
+        # Overlay the overridden weights targeting the item on a copy of the learned weights
+        for (source_item, target_item), weight in overrides:
+            if self.item_ids.get_id(target_item) != item_id:
+                continue
+            source_item_id = self.item_ids.get_id(source_item)
+            if source_item_id is not None and source_item_id < len(item_weights):
+                item_weights[source_item_id] = weight
+        return float(np.dot(user_vector, item_weights))
+
+    def _predict_pairs(self, pairs: List[Tuple[Any, Any]]) -> List[float]:
+        """
+        Predict scores for (user, item) pairs. Pairs with an unknown user or item are scored 0.0, unless strict is set.
+        :param pairs: List of (user, item) pairs
+        :return: List of predicted scores in the same order as pairs
+        """
+        scores = [0.0] * len(pairs)
+        # Group the pairs by user to score all items of a user at once
+        user_pairs: dict[int, List[Tuple[int, int]]] = {}
+        for i, (user, item) in enumerate(pairs):
+            user_id = self.user_ids.get_id(user)
+            item_id = self.item_ids.get_id(item)
+            if self.strict:
+                self._check_known(user, user_id, item, item_id)
+            if user_id is None or item_id is None:
+                continue
+            if user_id > self.interactions.max_user_id or item_id > self.interactions.max_item_id:
+                continue
+            user_pairs.setdefault(user_id, []).append((i, item_id))
+
+        if len(user_pairs) == 0:
+            return scores
+        interaction_matrix = self.interactions.to_csr(select_users=list(user_pairs.keys()))
+        for user_id, indexed_item_ids in user_pairs.items():
+            item_ids = [item_id for _, item_id in indexed_item_ids]
+            user_scores = np.asarray(self.model.predict_selected(user_id, item_ids, interaction_matrix)).ravel()
+            for (i, _), score in zip(indexed_item_ids, user_scores):
+                scores[i] = float(score)
+        return scores
+
+    def _recommended_item_ids(self, user: Any, top_k: int) -> List[int]:
+        """
+        Recommend top-K item indices for a known user.
+        :param user: User to recommend items for
+        :param top_k: Number of top items to recommend
+        :return: List of top-K item indices without blocked items, or an empty list if the user is unknown, unless strict is set
+        """
+        user_id = self._known_user_id(user)
+        if user_id is None:
+            return []
+        item_ids = self._recommend(user_id, top_k=top_k + self._num_excluded())
+        return self._drop_blocked(item_ids, top_k)
+
+    def _check_known(self, user: Any, user_id: Optional[int], item: Any, item_id: Optional[int]) -> None:
+        """
+        Raise KeyError naming the user or the item if it is unknown to the interactions.
+        :param user: User to check
+        :param user_id: User index, or None if the user is not identified
+        :param item: Item to check
+        :param item_id: Item index, or None if the item is not identified
+        """
+        if user_id is None or user_id > self.interactions.max_user_id:
+            raise KeyError(f"Unknown user: {user!r}")
+        if item_id is None or item_id > self.interactions.max_item_id:
+            raise KeyError(f"Unknown item: {item!r}")
+
+    def _known_user_id(self, user: Any) -> Optional[int]:
+        """
+        Resolve a user to its index if the user has interactions.
+        :param user: User to resolve
+        :return: User index, or None if the user is unknown
+        :raises KeyError: If the user is unknown and strict is set
+        """
+        user_id = self.user_ids.get_id(user)
+        if user_id is None or user_id > self.interactions.max_user_id:
+            if self.strict:
+                raise KeyError(f"Unknown user: {user!r}")
+            return None
+        return user_id
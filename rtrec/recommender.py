@@ -117,7 +117,7 @@ class Recommender:
         print(f"Throughput: {len(train_data) / (end_time - start_time):.2f} samples/sec")
         return self
 
-    def recommend(self, user: Any, candidate_items: Optional[List[Any]] = None, user_tags: Optional[List[str]] = None, top_k: int = 10, filter_interacted: bool = True) -> List[Any]:
+    def recommend(self, user: Any, candidate_items: Optional[List[Any]] = None, user_tags: Optional[List[str]] = None, top_k: int = 10, filter_interacted: bool = True, min_popularity: Optional[int] = None) -> List[Any]:
         """
         Recommend top-K items for a given user.
         :param user: User to recommend items for
@@ -125,9 +125,10 @@ class Recommender:
         :param user_tags: List of user tags
         :param top_k: Number of top items to recommend
         :param filter_interacted: Whether to filter out items the user has already interacted with
+        :param min_popularity: Minimum number of users who interacted with an item to recommend it
         :return: List of top-K item indices recommended for the user
         """
-        return self.model.recommend(user, candidate_items, user_tags, top_k, filter_interacted)
+        return self.model.recommend(user, candidate_items, user_tags, top_k, filter_interacted, min_popularity)
 
     def recommend_batch(self, users: List[Any], candidate_items: Optional[List[Any]] = None, users_tags: Optional[List[List[str]]] = None, top_k: int = 10, filter_interacted: bool = True) -> List[List[Any]]:
         """
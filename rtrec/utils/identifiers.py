@@ -1,3 +1,5 @@
+import sys
+from dataclasses import dataclass
 from typing import Any, Optional
 
 class IdentifierError(Exception):
@@ -5,16 +7,70 @@ class IdentifierError(Exception):
     def __init__(self, id_name: str, obj_id: int):
         super().__init__(f"Identifier not found for {id_name}: {obj_id}")
 
+class VocabularyFullError(Exception):
+    """Exception raised when a new object is identified beyond the vocabulary capacity."""
+    def __init__(self, id_name: str, max_vocab: int):
+        super().__init__(f"Vocabulary of {id_name} is full: {max_vocab}")
+
+@dataclass(frozen=True)
+class _NamespacedKey:
+    """Key of an object identified within a namespace, which never equals a bare object such as a tuple."""
+    namespace: str
+    obj: Any
+
 class Identifier:
-    def __init__(self, name: str="ID") -> None:
+    """
+    Maps arbitrary hashable objects to dense integer IDs.
+    IDs are allocated strictly in first-seen order, so identifying the same sequence of objects
+    always yields the same IDs regardless of hash randomization across processes.
+    """
+
+    def __init__(self, name: str="ID", max_vocab: Optional[int]=None) -> None:
         self.name = name
+        self.max_vocab = max_vocab # If set, new objects beyond this vocabulary size are rejected
+        self.num_rejected = 0 # Number of rejected identifications due to max_vocab
         self.obj_to_id: dict[Any, int] = {}  # Store object-to-ID mapping
         self.id_to_obj: list[Any] = []  # Store ID-to-object mapping
         self.pass_through : Optional[bool] = None # If True, return the object as-is if it is an integer
+        self.namespace: Optional[str] = None # If set, objects are identified within this namespace
+        self.namespace_ids: dict[str, set[int]] = {} # IDs of the objects identified within each namespace
+
+    def set_namespace(self, namespace: Optional[str]) -> None:
+        """
+        Set a namespace applied to every object before identification,
+        so that equal objects in different namespaces are assigned distinct IDs.
+        While a namespace is set, only the IDs identified within it are in_namespace.
+        :param namespace: Namespace to apply. If None, objects are identified as-is.
+        """
+        if namespace is not None and self.pass_through:
+            raise ValueError(f"Namespace cannot be used with pass-through integer IDs for {self.name}")
+        self.namespace = namespace
+
+    def _key(self, obj: Any) -> Any:
+        if self.namespace is None:
+            return obj
+        return _NamespacedKey(self.namespace, obj)
+
+    def in_namespace(self, obj_id: int) -> bool:
+        """
+        Check whether an ID was identified within the current namespace. Every ID is if no namespace is set.
+        :param obj_id: ID to check
+        """
+        if self.namespace is None:
+            return True
+        return obj_id in self.namespace_ids.get(self.namespace, ())
+
+    def num_outside_namespace(self) -> int:
+        """
+        Count the IDs identified outside the current namespace, which is 0 if no namespace is set.
+        """
+        if self.namespace is None:
+            return 0
+        return len(self.id_to_obj) - len(self.namespace_ids.get(self.namespace, ()))
 
     def identify(self, obj: Any) -> int:
         # If the object is an integer, return it as-is
-        if isinstance(obj, int):
+        if isinstance(obj, int) and self.namespace is None:
             if self.pass_through is False:
                 raise ValueError(f"Mixed types detected for {self.name}: {obj}")
             self.pass_through = True
@@ -24,25 +80,43 @@ class Identifier:
             raise ValueError(f"Mixed types detected for {self.name}: {obj}")
 
         # If the object is already in the set, find its ID
-        obj_id = self.obj_to_id.get(obj, None)
+        key = self._key(obj)
+        obj_id = self.obj_to_id.get(key, None)
         if obj_id is not None:
             return obj_id
 
         # Otherwise, assign a new ID
         new_id = len(self.id_to_obj)
-        self.obj_to_id[obj] = new_id
+        if self.max_vocab is not None and new_id >= self.max_vocab:
+            self.num_rejected += 1
+            raise VocabularyFullError(self.name, self.max_vocab)
+        self.obj_to_id[key] = new_id
         self.id_to_obj.append(obj)
+        if self.namespace is not None:
+            self.namespace_ids.setdefault(self.namespace, set()).add(new_id)
         self.pass_through = False  # Disable pass-through after adding non-integer objects
         return new_id
 
+    def check_capacity(self, obj: Any) -> None:
+        """
+        Raise VocabularyFullError if identifying an object would be rejected by max_vocab, without identifying it,
+        e.g., to reject an interaction before identifying either of its user and item.
+        :param obj: Object to check
+        """
+        if self.max_vocab is None or (isinstance(obj, int) and self.namespace is None):
+            return
+        if self._key(obj) not in self.obj_to_id and len(self.id_to_obj) >= self.max_vocab:
+            self.num_rejected += 1
+            raise VocabularyFullError(self.name, self.max_vocab)
+
     def get_id(self, obj: Any) -> Optional[int]:
         # If the object is an integer, return it as-is
-        if isinstance(obj, int):
+        if isinstance(obj, int) and self.namespace is None:
             if not self.pass_through:
                 raise ValueError(f"Mixed types detected for {self.name}: {obj}")
             return obj
 
-        return self.obj_to_id.get(obj, None)
+        return self.obj_to_id.get(self._key(obj), None)
 
     def get(self, obj_id: int) -> Any:
         # If pass_through is enabled, return the object as-is if it is an integer
@@ -64,6 +138,12 @@ class Identifier:
             return self.id_to_obj[obj_id]
         return default
 
+    def memory_usage(self) -> int:
+        """
+        Estimate the memory used by the ID mappings in bytes, excluding the identified objects themselves.
+        """
+        return sys.getsizeof(self.obj_to_id) + sys.getsizeof(self.id_to_obj) + sum(sys.getsizeof(ids) for ids in self.namespace_ids.values())
+
     def __getitem__(self, obj_id: int) -> Any:
         """Allow indexing to access objects by their ID."""
         return self.get(obj_id)  # Use the existing get method for retrieval
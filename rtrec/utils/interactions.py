@@ -1,13 +1,23 @@
 from collections import defaultdict
+from enum import Enum
 from typing import List, Optional, Any
 import time, math
 import logging
+import sys
 from datetime import datetime, timezone
 
 import numpy as np
 from scipy.sparse import csr_matrix, csc_matrix, coo_matrix
 from .lru import LRUFreqSet
 
+class RatingTransform(Enum):
+    """
+    Transformation of the stored ratings used for training and scoring, which compresses heavy-tailed counts.
+    The sign of a rating is preserved, e.g., log1p maps -x to -log(1 + x).
+    """
+    LOG1P = "log1p"
+    SQRT = "sqrt"
+
 class UserItemInteractions:
     def __init__(self, min_value: int = -5, max_value: int = 10, decay_in_days: Optional[int] = None, **kwargs: Any) -> None:
         """
@@ -18,12 +28,18 @@ class UserItemInteractions:
             max_value (int): Maximum allowable value for interactions.
             decay_rate (Optional[float]): Rate at which interactions decay over time.
                                           If None, no decay is applied.
+            rating_transform (Optional[RatingTransform | str]): Transformation applied to the accumulated ratings
+                                          when they are read for training and scoring. If None, ratings are used as is.
         """
         # Store interactions as a dictionary of dictionaries in shape {user_id: {item_id: (value, timestamp)}}
         self.interactions: defaultdict[int, dict[int, tuple[float, float]]] = defaultdict(dict)
         self.all_item_ids = set()
+        # Number of users who interacted with each item, maintained incrementally
+        self.item_counts: defaultdict[int, int] = defaultdict(int)
         n_recent_hot = kwargs.get("n_recent_hot", 100_000)
         self.hot_items = LRUFreqSet(capacity=n_recent_hot)
+        rating_transform = kwargs.get("rating_transform", None)
+        self.rating_transform = None if rating_transform is None else RatingTransform(rating_transform)
         assert max_value > min_value, f"max_value should be greater than min_value {max_value} > {min_value}"
         self.min_value = min_value
         self.max_value = max_value
@@ -75,6 +91,24 @@ class UserItemInteractions:
 
         return value * self.decay_rate ** elapsed_days # approximated exponential decay in time e^(-ln(2)/decay_in_days * elapsed_days)
 
+    def _rating(self, value: float, last_timestamp: float) -> float:
+        """
+        Computes the rating used for training and scoring from a stored interaction value.
+
+        Args:
+            value (float): The accumulated interaction value.
+            last_timestamp (float): The timestamp of the last interaction.
+
+        Returns:
+            float: The decayed and transformed interaction value.
+        """
+        value = self._apply_decay(value, last_timestamp)
+        if self.rating_transform is RatingTransform.LOG1P:
+            return math.copysign(math.log1p(abs(value)), value)
+        if self.rating_transform is RatingTransform.SQRT:
+            return math.copysign(math.sqrt(abs(value)), value)
+        return value
+
     def add_interaction(self, user_id: int, item_id: int, tstamp: float, delta: float = 1.0, upsert: bool = False) -> None:
         """
         Adds or updates an interaction count for a user-item pair.
@@ -95,10 +129,14 @@ class UserItemInteractions:
         # Update the maximum timestamp to avoid conflicts
         self.max_timestamp = max(self.max_timestamp, tstamp + 1.0)
 
+        if item_id not in self.interactions[user_id]:
+            self.item_counts[item_id] += 1
+
         if upsert:
             self.interactions[user_id][item_id] = (delta, tstamp)
         else:
-            current = self.get_user_item_rating(user_id, item_id, default_rating=0.0)
+            # Accumulate on the untransformed value so that the transformation is not compounded
+            current = self._apply_decay(*self.interactions[user_id][item_id]) if item_id in self.interactions[user_id] else 0.0
             new_value = current + delta
 
             # Clip the new value within the defined bounds
@@ -130,7 +168,7 @@ class UserItemInteractions:
         current, last_timestamp = self.interactions[user_id].get(item_id, (default_rating, 0.0))
         if current == default_rating:
             return default_rating  # Return default if no interaction exists
-        return self._apply_decay(current, last_timestamp)
+        return self._rating(current, last_timestamp)
 
     def get_user_items(self, user_id: int, n_recent: Optional[int] = None) -> List[int]:
         """
@@ -158,6 +196,22 @@ class UserItemInteractions:
         else:
             return list(user_interactions.keys())
 
+    def get_item_users(self, item_id: int) -> List[tuple[int, float]]:
+        """
+        Retrieves the users who interacted with a given item and their ratings, applying decay to each interaction.
+
+        Args:
+            item_id (int): ID of the item.
+
+        Returns:
+            List[tuple[int, float]]: List of (user ID, rating) pairs sorted by rating in descending order.
+        """
+        item_users = [
+            (user_id, self._rating(*inner_dict[item_id]))
+            for user_id, inner_dict in self.interactions.items() if item_id in inner_dict
+        ]
+        return sorted(item_users, key=lambda x: x[1], reverse=True)
+
     def get_all_item_ids(self) -> List[int]:
         """
         Retrieves a list of all unique item IDs.
@@ -221,6 +275,75 @@ class UserItemInteractions:
             interacted_items = self.get_user_items(user_id)
         return list(self.hot_items.get_freq_items(n, exclude_items=interacted_items))
 
+    def get_trending_items(self, n: int, half_life_days: Optional[float] = None) -> List[tuple[int, float]]:
+        """
+        Retrieves the top N items by time-decayed popularity, where each user's positive interaction
+        with an item counts as 1.0 decayed by its age relative to the latest interaction.
+
+        Args:
+            n (int): Number of items to retrieve.
+            half_life_days (Optional[float]): Half-life of an interaction in days. If None, the decay rate
+                of the interactions is used, and interactions are not decayed if it is not set either.
+
+        Returns:
+            List[tuple[int, float]]: List of (item ID, decayed popularity) pairs sorted by popularity in descending order.
+        """
+        if half_life_days is not None:
+            assert half_life_days > 0, f"half_life_days must be positive: {half_life_days}"
+            decay_rate = 0.5 ** (1.0 / half_life_days)
+        else:
+            decay_rate = self.decay_rate
+
+        popularity: dict[int, float] = defaultdict(float)
+        for inner_dict in self.interactions.values():
+            for item_id, (rating, tstamp) in inner_dict.items():
+                if rating <= 0:
+                    continue
+                if decay_rate is None:
+                    popularity[item_id] += 1.0
+                else:
+                    elapsed_days = (self.max_timestamp - tstamp) / 86400.0
+                    popularity[item_id] += decay_rate ** elapsed_days
+        return sorted(popularity.items(), key=lambda x: x[1], reverse=True)[:n]
+
+    def get_item_counts(self, item_ids: Optional[List[int]] = None) -> dict[int, int]:
+        """
+        Counts the number of users who interacted with each item.
+
+        Args:
+            item_ids (Optional[List[int]]): Item IDs to count. If None, count all items.
+
+        Returns:
+            dict[int, int]: Mapping of item IDs to the number of users who interacted with them.
+        """
+        if item_ids is None:
+            return dict(self.item_counts)
+        return {item_id: self.item_counts.get(item_id, 0) for item_id in item_ids}
+
+    def get_co_occurrences(self, item_id: int) -> dict[int, float]:
+        """
+        Computes co-occurrence scores between a given item and every other item.
+        The score is the fraction of users who interacted with item_id that also interacted with the other item.
+
+        Args:
+            item_id (int): ID of the item.
+
+        Returns:
+            dict[int, float]: Mapping of co-occurring item IDs to their co-occurrence scores in (0, 1].
+        """
+        counts: defaultdict[int, int] = defaultdict(int)
+        num_users = 0
+        for inner_dict in self.interactions.values():
+            if item_id not in inner_dict:
+                continue
+            num_users += 1
+            for other_item_id in inner_dict:
+                if other_item_id != item_id:
+                    counts[other_item_id] += 1
+        if num_users == 0:
+            return {}
+        return {other_item_id: count / num_users for other_item_id, count in counts.items()}
+
     def to_csr(self, select_users: List[int] = None, include_weights: bool = True) -> csr_matrix:
         rows, cols = [], []
 
@@ -231,13 +354,13 @@ class UserItemInteractions:
                     for item, (rating, tstamp) in self.interactions.get(user, {}).items():
                         rows.append(user)
                         cols.append(item)
-                        data.append(self._apply_decay(rating, tstamp))
+                        data.append(self._rating(rating, tstamp))
             else:
                 for user, inner_dict in self.interactions.items():
                     for item, (rating, tstamp) in inner_dict.items():
                         rows.append(user)
                         cols.append(item)
-                        data.append(self._apply_decay(rating, tstamp))
+                        data.append(self._rating(rating, tstamp))
             return csr_matrix((data, (rows, cols)), shape=(self.max_user_id + 1, self.max_item_id + 1), dtype="float32")
         else:
             if select_users:
@@ -253,6 +376,34 @@ class UserItemInteractions:
             data = np.ones(len(rows), dtype="int32")
             return csr_matrix((data, (rows, cols)), shape=(self.max_user_id + 1, self.max_item_id + 1), dtype="int32")
 
+    def temporal_split(self, cutoff: float) -> tuple[csr_matrix, dict[int, List[int]]]:
+        """
+        Splits interactions at a cutoff timestamp.
+
+        Only the latest timestamp of each user-item pair is kept, so a pair interacted with both before and
+        after the cutoff is entirely held out and is missing from the matrix, even though its earlier
+        interactions precede the cutoff.
+
+        Args:
+            cutoff (float): Interactions at or after this timestamp are held out.
+
+        Returns:
+            tuple[csr_matrix, dict[int, List[int]]]: The user-item matrix of interactions before the cutoff,
+            and the held-out item IDs of each user with interactions at or after the cutoff.
+        """
+        rows, cols, data = [], [], []
+        held_out: dict[int, List[int]] = {}
+        for user, inner_dict in self.interactions.items():
+            for item, (rating, tstamp) in inner_dict.items():
+                if tstamp < cutoff:
+                    rows.append(user)
+                    cols.append(item)
+                    data.append(self._rating(rating, tstamp))
+                else:
+                    held_out.setdefault(user, []).append(item)
+        matrix = csr_matrix((data, (rows, cols)), shape=(self.max_user_id + 1, self.max_item_id + 1), dtype="float32")
+        return matrix, held_out
+
     def to_csc(self, select_items: List[int] = None) -> csc_matrix:
         rows, cols, data = [], [], []
 
@@ -262,7 +413,7 @@ class UserItemInteractions:
                     continue
                 rows.append(user)
                 cols.append(item)
-                data.append(self._apply_decay(rating, tstamp))
+                data.append(self._rating(rating, tstamp))
 
         # Create the csc_matrix
         return csc_matrix((data, (rows, cols)), shape=(self.max_user_id + 1, self.max_item_id + 1), dtype="float32")
@@ -276,7 +427,7 @@ class UserItemInteractions:
                     for item, (rating, tstamp) in inner_dict.items():
                         rows.append(user)
                         cols.append(item)
-                        data.append(self._apply_decay(rating, tstamp))
+                        data.append(self._rating(rating, tstamp))
             else:
                 for user, inner_dict in self.interactions.items():
                     for item in select_items:
@@ -285,14 +436,14 @@ class UserItemInteractions:
                         rating, tstamp = inner_dict[item]
                         rows.append(user)
                         cols.append(item)
-                        data.append(self._apply_decay(rating, tstamp))
+                        data.append(self._rating(rating, tstamp))
         else:
             if select_items is None:
                 for user in select_users:
                     for item, (rating, tstamp) in self.interactions.get(user, {}).items():
                         rows.append(user)
                         cols.append(item)
-                        data.append(self._apply_decay(rating, tstamp))
+                        data.append(self._rating(rating, tstamp))
             else:
                 for user in select_users:
                     inner_dict = self.interactions.get(user, {})
@@ -302,11 +453,26 @@ class UserItemInteractions:
                         rating, tstamp = inner_dict[item]
                         rows.append(user)
                         cols.append(item)
-                        data.append(self._apply_decay(rating, tstamp))
+                        data.append(self._rating(rating, tstamp))
 
         # Create the coo_matrix
         return coo_matrix((data, (rows, cols)), shape=(self.max_user_id + 1, self.max_item_id + 1), dtype="float32")
 
+    def memory_usage(self) -> int:
+        """
+        Estimates the memory used by the stored interactions in bytes from the container sizes and the number of entries.
+
+        Returns:
+            int: Estimated memory usage in bytes.
+        """
+        # Each entry is a (rating, timestamp) tuple of floats keyed by an integer item ID
+        entry_size = sys.getsizeof((0.0, 0.0)) + 2 * sys.getsizeof(0.0) + sys.getsizeof(0)
+        total = sys.getsizeof(self.interactions)
+        for inner_dict in self.interactions.values():
+            total += sys.getsizeof(inner_dict) + len(inner_dict) * entry_size
+        total += sys.getsizeof(self.all_item_ids) + sys.getsizeof(self.item_counts)
+        return total
+
     @property
     def shape(self) -> tuple[int, int]:
         """
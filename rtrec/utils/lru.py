@@ -113,3 +113,68 @@ class LRUFreqSet(MutableSet):
         else:
             for key, _ in (sorted_items if n is None else sorted_items[:n]):
                 yield key
+
+class LRUCache:
+    def __init__(self, capacity: int):
+        """
+        Initialize the LRU cache.
+
+        Parameters:
+            capacity (int): Maximum number of entries in the cache.
+        """
+        if capacity <= 0:
+            raise ValueError("Capacity must be greater than 0.")
+
+        self.capacity = capacity
+        self.data: OrderedDict[Any, Any] = OrderedDict()
+
+    def get(self, key: Any, default: Optional[Any] = None) -> Any:
+        """
+        Retrieve the value for a key and mark it as recently used.
+
+        Parameters:
+            key (Any): The key to look up.
+            default (Optional[Any]): The value to return if the key is not cached.
+
+        Returns:
+            Any: The cached value, or default if the key is not cached.
+        """
+        if key not in self.data:
+            return default
+        self.data.move_to_end(key)
+        return self.data[key]
+
+    def put(self, key: Any, value: Any) -> None:
+        """
+        Cache a value for a key, evicting the least recently used entry if at capacity.
+
+        Parameters:
+            key (Any): The key to cache.
+            value (Any): The value to cache.
+        """
+        if key in self.data:
+            self.data.move_to_end(key)
+        elif len(self.data) >= self.capacity:
+            self.data.popitem(last=False)
+        self.data[key] = value
+
+    def invalidate(self, key: Any) -> None:
+        """
+        Remove a key from the cache if it exists.
+
+        Parameters:
+            key (Any): The key to remove.
+        """
+        self.data.pop(key, None)
+
+    def clear(self) -> None:
+        """
+        Remove all entries from the cache.
+        """
+        self.data.clear()
+
+    def __contains__(self, key: Any) -> bool:
+        return key in self.data
+
+    def __len__(self) -> int:
+        return len(self.data)
@@ -206,6 +206,41 @@ def auc(ranked_list: List[Any], ground_truth: List[Any], recommend_size: int) ->
     n_pairs = true_positives * false_positives
     return correct_pairs / n_pairs
 
+def score_auc(positive_scores: List[float], negative_scores: List[float]) -> float:
+    """
+    Computes the Area Under the ROC Curve (AUC) from the scores of labeled positive and negative samples,
+    i.e., the probability that a random positive is scored higher than a random negative.
+
+    Formula:
+        AUC = (sum(rank(p) for p in positives) - n_pos * (n_pos + 1) / 2) / (n_pos * n_neg)
+
+    where ranks are 1-based in ascending order of scores and tied scores share their average rank.
+
+    Parameters:
+        positive_scores: Scores of positive samples.
+        negative_scores: Scores of negative samples.
+
+    Returns:
+        AUC score as a float. 0.0 if either of the samples is empty.
+    """
+    n_pos, n_neg = len(positive_scores), len(negative_scores)
+    if n_pos == 0 or n_neg == 0:
+        return 0.0
+
+    scored = sorted([(score, True) for score in positive_scores] + [(score, False) for score in negative_scores], key=lambda x: x[0])
+    positive_rank_sum = 0.0
+    i = 0
+    while i < len(scored):
+        # find the group of tied scores
+        j = i
+        while j + 1 < len(scored) and scored[j + 1][0] == scored[i][0]:
+            j += 1
+        average_rank = (i + j) / 2.0 + 1.0
+        positive_rank_sum += average_rank * sum(1 for k in range(i, j + 1) if scored[k][1])
+        i = j + 1
+
+    return (positive_rank_sum - n_pos * (n_pos + 1) / 2.0) / (n_pos * n_neg)
+
 def average_precision(ranked_list: List[Any], ground_truth: List[Any], recommend_size: int) -> float:
     """
     Computes the Average Precision (AP) for a ranked list of items.
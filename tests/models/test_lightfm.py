@@ -25,6 +25,16 @@ def test_similar_items_no_data(model):
     # Since there are no items, similar items should be empty
     assert similar_items == ['item_2'] # Unlike SLIM, LightFM is intented to return item_2 while it is not interacted with item_1
 
+def test_fit_max_vocab_rejects_whole_interaction():
+    current_unixtime = time.time()
+    model = LightFM(random_state=42, epochs=10, max_vocab=2)
+    model.fit([('user_1', 'item_1', current_unixtime, 5.0),
+               ('user_1', 'item_2', current_unixtime, 4.0),
+               ('user_2', 'item_3', current_unixtime, 3.0)])
+    # user_2 is not registered as its only item is rejected
+    assert model.user_ids.get_id('user_2') is None
+    assert model.item_ids.num_rejected == 1
+
 def test_fit_and_recommend(model):
     import time
     current_unixtime = time.time()
@@ -67,6 +77,10 @@ def test_similar_items(model):
     assert similar_items == ["item_4", "item_3", "item_2"]
     assert scores[2] < 0  # item_2 should have a negative score
 
+    # Options of other models such as SLIM's blend are rejected
+    with pytest.raises(ValueError):
+        model.similar_items('item_1', top_k=5, blend=0.5)
+
 def test_fit_and_recommend_batch(model):
     import time
     current_unixtime = time.time()
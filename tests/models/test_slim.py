@@ -2,13 +2,26 @@
 
 import pytest
 
-from rtrec.models.slim import SLIM
+from rtrec.models.slim import SLIM, SLIMConfig
 
 @pytest.fixture
 def model():
     model = SLIM()
     return model
 
+def test_from_config():
+    default_model = SLIM()
+    model = SLIM.from_config(SLIMConfig())
+    assert model.model.alpha == default_model.model.alpha
+    assert model.model.l1_ratio == default_model.model.l1_ratio
+    assert model.interactions.max_value == default_model.interactions.max_value
+    assert model.missing_similarity == default_model.missing_similarity
+
+    model = SLIM.from_config(SLIMConfig(alpha=0.5, decay_in_days=7, min_profile_signal=1.0))
+    assert model.model.alpha == 0.5
+    assert model.interactions.decay_rate is not None
+    assert model.min_profile_signal == 1.0
+
 def test_register_user_feature(model):
     user_id = 'user_1'
     user_tags = ['tag1', 'tag2']
@@ -33,6 +46,16 @@ def test_add_interactions(model):
     item_index = model.item_ids.identify('item_1')
     assert model.interactions.get_user_item_rating(user_index, item_index) == 5.0
 
+def test_item_users(model):
+    interactions = [('user_1', 'item_1', 1622470427.0, 5.0),
+                    ('user_2', 'item_1', 1622470427.0, 3.0),
+                    ('user_3', 'item_1', 1622470427.0, 4.0),
+                    ('user_3', 'item_2', 1622470427.0, 4.0)]
+    model.add_interactions(interactions)
+    assert model.item_users('item_1') == [('user_1', 5.0), ('user_3', 4.0), ('user_2', 3.0)]
+    assert model.item_users('item_1', limit=1) == [('user_1', 5.0)]
+    assert model.item_users('unknown_item') == []
+
 def test_recommend_no_interactions(model):
     recommendations = model.recommend('user_1', top_k=5)
     # Since there are no interactions, recommendations should be empty
@@ -77,6 +100,47 @@ def test_similar_items(model):
     assert similar_items == ["item_4", "item_3"]
     assert scores[0] > scores[1]
 
+def test_fit_without_retaining_interactions(model):
+    import time
+    current_unixtime = time.time()
+    interactions = [('user_1', 'item_1', current_unixtime, 5.0),
+                   ('user_1', 'item_3', current_unixtime, 4.0),
+                   ('user_1', 'item_4', current_unixtime, 3.0),
+                   ('user_2', 'item_1', current_unixtime, 3.0),
+                   ('user_2', 'item_2', current_unixtime, -2.0),
+                   ('user_2', 'item_4', current_unixtime, 3.0),
+                   ('user_3', 'item_1', current_unixtime, 4.0),
+                   ('user_3', 'item_3', current_unixtime, 2.0),
+                   ('user_3', 'item_4', current_unixtime, 4.0)]
+    model.fit(interactions, retain_interactions=False)
+
+    assert len(model.interactions.interactions) == 0
+    assert model.similar_items('item_1', top_k=5) == ["item_4", "item_3"]
+    # Users without stored interactions are unknown and get the (empty) popular items
+    assert model.user_ids.get_id('user_2') is None
+    assert model.recommend('user_2', top_k=5) == []
+    assert model.recommend_batch(['user_1', 'user_3'], top_k=5) == [[], []]
+
+def test_fit_without_retaining_interactions_rating():
+    import time
+    import numpy as np
+    current_unixtime = time.time()
+    # Repeated interactions are accumulated and clipped before the transformation
+    interactions = [('user_1', 'item_1', current_unixtime, 8.0),
+                   ('user_1', 'item_1', current_unixtime, 8.0),
+                   ('user_1', 'item_3', current_unixtime, 4.0),
+                   ('user_2', 'item_1', current_unixtime, 3.0),
+                   ('user_2', 'item_2', current_unixtime, -2.0),
+                   ('user_2', 'item_3', current_unixtime, 3.0),
+                   ('user_3', 'item_2', current_unixtime, 4.0),
+                   ('user_3', 'item_3', current_unixtime, 2.0)]
+    retaining_model = SLIM(rating_transform="log1p", decay_in_days=7)
+    retaining_model.fit(interactions)
+    model = SLIM(rating_transform="log1p", decay_in_days=7)
+    model.fit(interactions, retain_interactions=False)
+
+    assert np.allclose(model.model.item_similarity.toarray(), retaining_model.model.item_similarity.toarray())
+
 def test_fit_and_recommend(model):
     import time
     current_unixtime = time.time()
@@ -96,5 +160,954 @@ def test_fit_and_recommend(model):
     # Verify that the recommendations are correct
     assert recommendations == ["item_4", "item_2"]
 
+def test_max_vocab():
+    import time
+    current_unixtime = time.time()
+    model = SLIM(max_vocab=2)
+    interactions = [('user_1', 'item_1', current_unixtime, 5.0),
+                   ('user_1', 'item_2', current_unixtime, 4.0),
+                   ('user_1', 'item_3', current_unixtime, 3.0),
+                   ('user_2', 'item_1', current_unixtime, 3.0),
+                   ('user_3', 'item_1', current_unixtime, 3.0)]
+    model.add_interactions(interactions)
+    assert model.item_ids.get_id('item_3') is None
+    assert model.user_ids.get_id('user_3') is None
+    assert model.item_ids.num_rejected == 1
+    assert model.user_ids.num_rejected == 1
+
+def test_max_vocab_rejects_whole_interaction():
+    import time
+    current_unixtime = time.time()
+    model = SLIM(max_vocab=2)
+    interactions = [('user_1', 'item_1', current_unixtime, 5.0),
+                   ('user_1', 'item_2', current_unixtime, 4.0),
+                   ('user_2', 'item_3', current_unixtime, 3.0)]
+    model.fit(interactions)
+    # user_2 is not registered as its only item is rejected
+    assert model.user_ids.get_id('user_2') is None
+    assert model.item_ids.num_rejected == 1
+    assert sorted(model.recommend('user_2', top_k=5)) == ["item_1", "item_2"]
+
+    # Looking up unknown items neither registers nor rejects them
+    assert model.similar_items('item_4') == []
+    assert model.item_ids.get_id('item_4') is None
+    assert model.item_ids.num_rejected == 1
+
+def test_max_users_and_max_items():
+    import time
+    current_unixtime = time.time()
+    model = SLIM(max_vocab=2, max_items=3)
+    interactions = [('user_1', 'item_1', current_unixtime, 5.0),
+                   ('user_1', 'item_2', current_unixtime, 4.0),
+                   ('user_1', 'item_3', current_unixtime, 3.0),
+                   ('user_2', 'item_1', current_unixtime, 3.0),
+                   ('user_3', 'item_1', current_unixtime, 3.0)]
+    model.add_interactions(interactions)
+    assert model.item_ids.get_id('item_3') == 2
+    assert model.user_ids.get_id('user_3') is None
+    assert model.item_ids.num_rejected == 0
+    assert model.user_ids.num_rejected == 1
+
+def test_fit_events():
+    import time
+    current_unixtime = time.time()
+    model = SLIM(event_weights={"view": 1.0, "cart": 3.0, "purchase": 5.0})
+    events = [('user_1', 'item_1', current_unixtime, "purchase"),
+              ('user_1', 'item_2', current_unixtime, "view"),
+              ('user_2', 'item_1', current_unixtime, "cart"),
+              ('user_2', 'item_3', current_unixtime, "unknown")]
+    model.fit_events(events)
+
+    user_1 = model.user_ids.get_id('user_1')
+    user_2 = model.user_ids.get_id('user_2')
+    assert model.interactions.get_user_item_rating(user_1, model.item_ids.get_id('item_1')) == 5.0
+    assert model.interactions.get_user_item_rating(user_1, model.item_ids.get_id('item_2')) == 1.0
+    assert model.interactions.get_user_item_rating(user_2, model.item_ids.get_id('item_1')) == 3.0
+    # Events of unknown types are skipped
+    assert model.item_ids.get_id('item_3') is None
+
+def test_fit_csr(model):
+    import time
+    from scipy.sparse import csr_matrix
+    current_unixtime = time.time()
+    ui = csr_matrix(([5.0, 4.0, 3.0, 3.0, 4.0, 4.0], ([0, 0, 1, 1, 2, 2], [0, 1, 0, 2, 0, 2])), shape=(3, 3))
+    timestamps = [current_unixtime] * ui.nnz
+    model.fit_csr(ui.indptr, ui.indices, ui.data, timestamps)
+    assert model.interactions.get_user_item_rating(1, 2) == 3.0
+    assert model.recommend(0, top_k=5) == [2]
+
+    with pytest.raises(ValueError):
+        model.fit_csr([0, 2, 1, 2], [0, 1], [1.0, 1.0], timestamps[:2]) # non-monotonic indptr
+    with pytest.raises(ValueError):
+        model.fit_csr(ui.indptr, ui.indices, ui.data, timestamps[:2]) # length mismatch
+    with pytest.raises(ValueError):
+        model.fit_csr([0, 2], [0, -1], [1.0, 1.0], timestamps[:2]) # negative index
+
+    # Non-integer IDs are rejected before adding any interaction
+    model = SLIM()
+    model.add_interactions([('user_1', 'item_1', current_unixtime, 5.0)])
+    with pytest.raises(ValueError, match="user"):
+        model.fit_csr(ui.indptr, ui.indices, ui.data, timestamps)
+    assert len(model.interactions.get_all_users()) == 1
+
+def test_similar_items_blend():
+    import time
+    current_unixtime = time.time()
+    interactions = [('user_1', 'item_1', current_unixtime, 5.0),
+                   ('user_1', 'item_3', current_unixtime, 4.0),
+                   ('user_1', 'item_4', current_unixtime, 3.0),
+                   ('user_2', 'item_1', current_unixtime, 3.0),
+                   ('user_2', 'item_2', current_unixtime, -2.0),
+                   ('user_2', 'item_4', current_unixtime, 3.0),
+                   ('user_3', 'item_1', current_unixtime, 4.0),
+                   ('user_3', 'item_3', current_unixtime, 2.0),
+                   ('user_3', 'item_4', current_unixtime, 4.0)]
+
+    model = SLIM()
+    model.fit(interactions)
+    # Pure co-occurrence
+    results = model.similar_items('item_1', top_k=5, ret_scores=True, blend=0.0)
+    assert results == [("item_4", pytest.approx(1.0)), ("item_3", pytest.approx(2.0 / 3.0)), ("item_2", pytest.approx(1.0 / 3.0))]
+
+    # Pure SLIM
+    results = model.similar_items('item_1', top_k=5, ret_scores=True, blend=1.0)
+    assert [item for item, score in results if score > 0.0] == ["item_4", "item_3"]
+
+    unfitted_model = SLIM()
+    unfitted_model.add_interactions(interactions)
+    with pytest.raises(RuntimeError):
+        unfitted_model.similar_items('item_1', blend=0.5)
+
+def test_auc(model):
+    import time
+    current_unixtime = time.time()
+    interactions = [('user_1', 'item_1', current_unixtime, 5.0),
+                   ('user_1', 'item_3', current_unixtime, 4.0),
+                   ('user_1', 'item_4', current_unixtime, 3.0),
+                   ('user_2', 'item_1', current_unixtime, 3.0),
+                   ('user_2', 'item_4', current_unixtime, 3.0),
+                   ('user_3', 'item_1', current_unixtime, 4.0),
+                   ('user_3', 'item_3', current_unixtime, 2.0),
+                   ('user_3', 'item_4', current_unixtime, 4.0),
+                   ('user_4', 'item_2', current_unixtime, 4.0)]
+    model.fit(interactions)
+
+    positives = [('user_2', 'item_3')]
+    negatives = [('user_2', 'item_2'), ('unknown_user', 'item_1')]
+    assert model.auc(positives, negatives) == pytest.approx(1.0)
+    assert model.auc(negatives, positives) == pytest.approx(0.0)
+
+def test_temporal_eval(model):
+    import time
+    cutoff = time.time() - 3600
+    past = cutoff - 3600
+    interactions = [('user_1', 'item_1', past, 5.0),
+                   ('user_1', 'item_3', past, 4.0),
+                   ('user_2', 'item_1', past, 3.0),
+                   ('user_2', 'item_4', past, 3.0),
+                   ('user_3', 'item_1', past, 4.0),
+                   ('user_3', 'item_3', past, 2.0),
+                   ('user_3', 'item_4', past, 4.0),
+                   ('user_4', 'item_2', past, 4.0)]
+    model.fit(interactions)
+    # Held-out interaction of user_2 after the cutoff
+    model.add_interactions([('user_2', 'item_3', cutoff + 60, 5.0)])
+
+    metrics = model.temporal_eval(cutoff, top_k=1)
+    assert metrics["recall"] == 1.0
+    assert metrics["mrr"] == 1.0
+
+def test_predict_with_weight_overrides(model):
+    import time
+    current_unixtime = time.time()
+    interactions = [('user_1', 'item_1', current_unixtime, 5.0),
+                   ('user_1', 'item_3', current_unixtime, 4.0),
+                   ('user_2', 'item_1', current_unixtime, 3.0),
+                   ('user_2', 'item_4', current_unixtime, 3.0),
+                   ('user_3', 'item_2', current_unixtime, 2.0)]
+    model.fit(interactions)
+    similarity = model.model.item_similarity.copy()
+
+    # user_1 scores item_2 by 5.0 * w(item_1, item_2) + 4.0 * w(item_3, item_2)
+    overrides = [(('item_1', 'item_2'), 0.5), (('item_3', 'item_2'), 0.25), (('item_1', 'item_4'), 10.0)]
+    assert model.predict_with_weight_overrides('user_1', 'item_2', overrides) == pytest.approx(3.5)
+    assert model.predict_with_weight_overrides('unknown_user', 'item_2', overrides) == 0.0
+
+    # The model itself is left untouched
+    assert (model.model.item_similarity != similarity).nnz == 0
+
+    # An item added after the fit has no learned similarities
+    model.add_interactions([('user_2', 'item_5', current_unixtime, 1.0)])
+    assert model.predict_with_weight_overrides('user_1', 'item_5', []) == 0.0
+    assert model.predict_with_weight_overrides('user_1', 'item_5', [(('item_1', 'item_5'), 0.5)]) == pytest.approx(2.5)
+
+    unfitted_model = SLIM()
+    unfitted_model.add_interactions(interactions)
+    with pytest.raises(RuntimeError):
+        unfitted_model.predict_with_weight_overrides('user_1', 'item_2', overrides)
+
+def test_memory_usage(model):
+    import time
+    current_unixtime = time.time()
+    assert model.memory_usage()["weights"] == 0
+
+    interactions = [('user_1', 'item_1', current_unixtime, 5.0),
+                   ('user_1', 'item_3', current_unixtime, 4.0),
+                   ('user_2', 'item_1', current_unixtime, 3.0),
+                   ('user_2', 'item_3', current_unixtime, 3.0)]
+    model.fit(interactions)
+    usage = model.memory_usage()
+    assert set(usage.keys()) == {"weights", "interactions", "user_ids", "item_ids"}
+    assert all(size > 0 for size in usage.values())
+
+    before = usage["interactions"]
+    model.add_interactions([('user_3', 'item_1', current_unixtime, 1.0)])
+    assert model.memory_usage()["interactions"] > before
+
+def test_recommendable_coverage(model):
+    import time
+    current_unixtime = time.time()
+    assert model.recommendable_coverage() == 0.0
+
+    interactions = [('user_1', 'item_1', current_unixtime, 5.0),
+                   ('user_1', 'item_3', current_unixtime, 4.0),
+                   ('user_2', 'item_1', current_unixtime, 3.0),
+                   ('user_2', 'item_3', current_unixtime, 3.0),
+                   ('user_3', 'item_2', current_unixtime, 2.0)]
+    model.fit(interactions)
+    # item_2 never co-occurs with other items, so it has no learned similarity
+    assert model.recommendable_coverage() == pytest.approx(2.0 / 3.0)
+
+def test_prune_to_size(model):
+    import time
+    current_unixtime = time.time()
+    interactions = [('user_1', 'item_1', current_unixtime, 5.0),
+                   ('user_1', 'item_3', current_unixtime, 4.0),
+                   ('user_1', 'item_4', current_unixtime, 3.0),
+                   ('user_2', 'item_1', current_unixtime, 3.0),
+                   ('user_2', 'item_4', current_unixtime, 3.0),
+                   ('user_3', 'item_1', current_unixtime, 4.0),
+                   ('user_3', 'item_3', current_unixtime, 2.0),
+                   ('user_3', 'item_4', current_unixtime, 4.0)]
+    model.fit(interactions)
+    item_similarity = model.model.item_similarity
+    nnz = item_similarity.nnz
+    assert nnz > 2
+    largest = sorted(abs(item_similarity.data), reverse=True)[:2]
+
+    assert model.prune_to_size(nnz) == 0
+    assert model.prune_to_size(2) == nnz - 2
+    assert sorted(abs(model.model.item_similarity.data), reverse=True) == largest
+
+def test_snapshot(model):
+    import time
+    current_unixtime = time.time()
+    interactions = [('user_1', 'item_1', current_unixtime, 5.0),
+                   ('user_2', 'item_1', current_unixtime, 3.0),
+                   ('user_2', 'item_4', current_unixtime, 3.0),
+                   ('user_1', 'item_3', current_unixtime, 4.0)]
+    model.fit(interactions)
+    serving = model.snapshot()
+    expected = serving.recommend('user_1', top_k=5)
+
+    model.fit([('user_1', 'item_5', current_unixtime, 5.0), ('user_3', 'item_5', current_unixtime, 5.0)])
+    # The snapshot must not observe updates applied to the original model
+    assert serving.recommend('user_1', top_k=5) == expected
+    assert serving.item_ids.get_id('item_5') is None
+
+    # A serving view shares the item similarities until the model is fitted again
+    serving = model.snapshot(share_weights=True)
+    assert serving.model.item_similarity is model.model.item_similarity
+    assert serving.interactions is not model.interactions
+    expected = serving.recommend('user_1', top_k=5)
+    model.fit([('user_1', 'item_6', current_unixtime, 5.0), ('user_3', 'item_6', current_unixtime, 5.0)])
+    assert serving.model.item_similarity is not model.model.item_similarity
+    assert serving.recommend('user_1', top_k=5) == expected
+
+def test_recommend_filtered(model):
+    import time
+    current_unixtime = time.time()
+    interactions = [('user_1', 'item_1', current_unixtime, 5.0),
+                   ('user_2', 'item_2', current_unixtime, -2.0),
+                   ('user_2', 'item_1', current_unixtime, 3.0),
+                   ('user_2', 'item_4', current_unixtime, 3.0),
+                   ('user_1', 'item_3', current_unixtime, 4.0)]
+    model.fit(interactions)
+    assert model.recommend('user_1', top_k=5) == ["item_4", "item_2"]
+
+    assert model.recommend_filtered('user_1', lambda item: item != "item_4", top_k=5) == ["item_2"]
+    assert model.recommend_filtered('user_1', lambda item: False, top_k=5) == []
+
+def test_recommend_above(model):
+    import time
+    current_unixtime = time.time()
+    interactions = [('user_1', 'item_1', current_unixtime, 5.0),
+                   ('user_1', 'item_3', current_unixtime, 4.0),
+                   ('user_2', 'item_1', current_unixtime, 3.0),
+                   ('user_2', 'item_4', current_unixtime, 3.0),
+                   ('user_3', 'item_1', current_unixtime, 4.0),
+                   ('user_3', 'item_3', current_unixtime, 2.0),
+                   ('user_3', 'item_4', current_unixtime, 4.0),
+                   ('user_4', 'item_2', current_unixtime, 4.0)]
+    model.fit(interactions)
+
+    results = model.recommend_above('user_2', min_score=1e-6)
+    assert [item for item, _ in results] == ["item_3"]
+    assert results[0][1] > 0.0
+    # item_2 scores zero, so it is only returned when min_score allows it
+    assert len(model.recommend_above('user_2', min_score=0.0)) == 2
+    assert model.recommend_above('user_2', min_score=1e6) == []
+    assert model.recommend_above('user_2', min_score=0.0, max_items=1) == results
+    assert model.recommend_above('unknown_user', min_score=0.0) == []
+
+def test_score_stats(model):
+    model.add_interactions([('user_1', 'item_a', 1.0, 2.0),
+                            ('user_2', 'item_b', 1.0, 1.0),
+                            ('user_2', 'item_c', 1.0, 1.0),
+                            ('user_2', 'item_d', 1.0, 1.0)])
+    # item_a -> item_b: 0.5, item_a -> item_c: 1.0, item_a -> item_d: 1.5
+    model.import_similarity_matrix(["item_a", "item_b", "item_c", "item_d"], indptr=[0, 3, 3, 3, 3], indices=[1, 2, 3], data=[0.5, 1.0, 1.5])
+
+    stats = model.score_stats('user_1')
+    assert stats["min"] == pytest.approx(1.0)
+    assert stats["max"] == pytest.approx(3.0)
+    assert stats["mean"] == pytest.approx(2.0)
+    assert stats["p50"] == pytest.approx(2.0)
+    assert model.score_stats('user_1', filter_interacted=False)["min"] == pytest.approx(0.0)
+    assert model.score_stats('unknown_user') == {}
+
+def test_recommend_grouped(model):
+    import time
+    current_unixtime = time.time()
+    interactions = [('user_1', 'item_1', current_unixtime, 5.0),
+                   ('user_1', 'item_2', current_unixtime, 4.0),
+                   ('user_2', 'item_1', current_unixtime, 5.0),
+                   ('user_2', 'item_3', current_unixtime, 5.0),
+                   ('user_3', 'item_2', current_unixtime, 5.0),
+                   ('user_3', 'item_4', current_unixtime, 5.0),
+                   ('user_4', 'item_1', current_unixtime, 4.0),
+                   ('user_4', 'item_3', current_unixtime, 4.0),
+                   ('user_5', 'item_6', current_unixtime, 3.0),
+                   ('user_6', 'item_2', current_unixtime, 3.0),
+                   ('user_6', 'item_4', current_unixtime, 3.0),
+                   ('user_7', 'item_5', current_unixtime, 3.0),
+                   ('user_7', 'item_6', current_unixtime, 3.0)]
+    model.fit(interactions)
+
+    groups = model.recommend_grouped('user_1', per_source_k=2)
+    assert [source for source, _ in groups] == ["item_1", "item_2"]
+    assert [item for item, _ in groups[0][1]] == ["item_3"]
+    assert [item for item, _ in groups[1][1]] == ["item_4"]
+    assert model.recommend_grouped('unknown_user') == []
+
+def test_recommend_with_reason(model):
+    model.add_interactions([('user_1', 'item_a', 1.0, 5.0),
+                            ('user_1', 'item_b', 1.0, 2.0),
+                            ('user_2', 'item_c', 1.0, 1.0),
+                            ('user_2', 'item_d', 1.0, 1.0),
+                            ('user_2', 'item_e', 1.0, 1.0)])
+    # item_a -> item_c: 0.1, item_a -> item_d: 0.4, item_b -> item_c: 0.5
+    model.import_similarity_matrix(["item_a", "item_b", "item_c", "item_d"], indptr=[0, 2, 3, 3, 3], indices=[2, 3, 2], data=[0.1, 0.4, 0.5])
+
+    assert model.recommend_with_reason('user_1', top_k=3) == [
+        ("item_d", pytest.approx(2.0), "item_a"),
+        ("item_c", pytest.approx(1.5), "item_b"),
+        ("item_e", 0.0, None)
+    ]
+    assert model.recommend_with_reason('unknown_user') == []
+
+def test_user_affinity(model):
+    model.add_interactions([('user_1', 'item_a', 1.0, 2.0),
+                            ('user_1', 'item_b', 1.0, 2.0),
+                            ('user_2', 'item_b', 1.0, 1.0),
+                            ('user_2', 'item_c', 1.0, 1.0)])
+    # item_a -> item_c: 0.5, item_c -> item_a: 0.25
+    model.import_similarity_matrix(["item_a", "item_b", "item_c"], indptr=[0, 1, 1, 2], indices=[2, 0], data=[0.5, 0.25])
+
+    affinity = model.user_affinity('user_1', 'user_2')
+    assert affinity["shared_items"] == 1.0
+    assert affinity["jaccard"] == pytest.approx(1.0 / 3.0)
+    # user_1 scores item_b 0.0 and item_c 1.0, user_2 scores item_a 0.25 and item_b 0.0
+    assert affinity["a_to_b"] == pytest.approx(0.5)
+    assert affinity["b_to_a"] == pytest.approx(0.125)
+    assert affinity["affinity"] == pytest.approx(0.3125)
+    assert model.user_affinity('user_1', 'unknown_user') == {}
+
+def test_batch_explain(model):
+    model.add_interactions([('user_1', 'item_a', 1.0, 2.0),
+                            ('user_1', 'item_b', 1.0, 4.0),
+                            ('user_2', 'item_a', 1.0, 1.0),
+                            ('user_3', 'item_c', 1.0, 1.0)])
+    # item_a -> item_c: 1.0, item_b -> item_c: 0.25
+    model.import_similarity_matrix(["item_a", "item_b", "item_c"], indptr=[0, 1, 2, 2], indices=[2, 2], data=[1.0, 0.25])
+
+    assert model.batch_explain(['user_1', 'user_2', 'user_3', 'unknown_user'], 'item_c') == [
+        [("item_a", pytest.approx(2.0)), ("item_b", pytest.approx(1.0))],
+        [("item_a", pytest.approx(1.0))],
+        [],
+        []
+    ]
+    assert model.batch_explain(['user_1'], 'unknown_item') == [[]]
+
+def test_export_recommendations(model, tmp_path):
+    import pandas as pd
+    model.add_interactions([('user_1', 'item_a', 1.0, 5.0),
+                            ('user_2', 'item_b', 1.0, 2.0)])
+    # item_a -> item_b: 0.5, item_b -> item_a: 0.25
+    model.import_similarity_matrix(["item_a", "item_b"], indptr=[0, 1, 2], indices=[1, 0], data=[0.5, 0.25])
+
+    path = tmp_path / "recommendations.csv"
+    assert model.export_recommendations(str(path), top_k=5) == 2
+    df = pd.read_csv(path)
+    assert list(df.columns) == ["user", "rank", "item", "score"]
+    assert df.values.tolist() == [["user_1", 1, "item_b", 2.5], ["user_2", 1, "item_a", 0.5]]
+
+    # Scoring users in batches does not change the recommendations
+    assert model.export_recommendations(str(path), top_k=5, batch_size=1) == 2
+    assert pd.read_csv(path).values.tolist() == df.values.tolist()
+
+def test_item_embeddings(model):
+    import numpy as np
+    model.add_interactions([('user_1', 'item_a', 1.0, 1.0),
+                            ('user_1', 'item_b', 1.0, 1.0),
+                            ('user_2', 'item_c', 1.0, 1.0)])
+    # item_a <-> item_b: 0.5, item_c -> item_a: 0.1
+    model.import_similarity_matrix(["item_a", "item_b", "item_c"], indptr=[0, 1, 2, 3], indices=[1, 0, 0], data=[0.5, 0.5, 0.1])
+
+    items, embeddings = model.item_embeddings(dim=2)
+    assert items == ["item_a", "item_b", "item_c"]
+    assert embeddings.shape == (3, 2)
+    assert embeddings.dtype == np.float32
+    # Deterministic across calls
+    assert np.array_equal(model.item_embeddings(dim=2)[1], embeddings)
+
+    with pytest.raises(ValueError):
+        model.item_embeddings(dim=3)
+
+def test_matrix_spectrum(model):
+    model.add_interactions([('user_1', 'item_a', 1.0, 1.0),
+                            ('user_1', 'item_b', 1.0, 1.0),
+                            ('user_2', 'item_c', 1.0, 1.0)])
+    # Permutation of weights 3.0, 2.0 and 1.0 whose singular values are the weights
+    model.import_similarity_matrix(["item_a", "item_b", "item_c"], indptr=[0, 1, 2, 3], indices=[1, 2, 0], data=[3.0, 2.0, 1.0])
+
+    assert model.matrix_spectrum(2) == [pytest.approx(3.0), pytest.approx(2.0)]
+    with pytest.raises(ValueError):
+        model.matrix_spectrum(3)
+
+def test_score_cache(model):
+    import time
+    current_unixtime = time.time()
+    interactions = [('user_1', 'item_1', current_unixtime, 5.0),
+                   ('user_2', 'item_2', current_unixtime, -2.0),
+                   ('user_2', 'item_1', current_unixtime, 3.0),
+                   ('user_2', 'item_4', current_unixtime, 3.0),
+                   ('user_1', 'item_3', current_unixtime, 4.0)]
+    model.fit(interactions)
+    expected = model.recommend('user_1', top_k=5)
+
+    model.enable_score_cache(capacity=10)
+    assert model.recommend('user_1', top_k=5) == expected
+    assert model.user_ids.get_id('user_1') in model.score_cache
+    # Cached scores are reused with different filters
+    assert model.recommend('user_1', top_k=1) == expected[:1]
+    assert len(model.recommend('user_1', top_k=5, filter_interacted=False)) == 4
+
+    # Any update invalidates the cached scores
+    model.fit([('user_1', 'item_4', current_unixtime, 5.0)])
+    assert len(model.score_cache) == 0
+    assert "item_4" not in model.recommend('user_1', top_k=5)
+
+def test_rec_cache(model):
+    import time
+    current_unixtime = time.time()
+    interactions = [('user_1', 'item_1', current_unixtime, 5.0),
+                   ('user_2', 'item_2', current_unixtime, -2.0),
+                   ('user_2', 'item_1', current_unixtime, 3.0),
+                   ('user_2', 'item_4', current_unixtime, 3.0),
+                   ('user_1', 'item_3', current_unixtime, 4.0)]
+    model.fit(interactions)
+    model.enable_rec_cache(ttl_seconds=60, capacity=10)
+
+    expected = model.recommend('user_1', top_k=5)
+    assert model.recommend('user_1', top_k=5) == expected
+    model.recommend('user_2', top_k=5)
+    user_1_id, user_2_id = model.user_ids.get_id('user_1'), model.user_ids.get_id('user_2')
+    assert user_1_id in model.rec_cache and user_2_id in model.rec_cache
+
+    # Only the entries of users touched by the fit are invalidated
+    model.fit([('user_2', 'item_3', current_unixtime, 1.0)])
+    assert user_1_id in model.rec_cache
+    assert user_2_id not in model.rec_cache
+
+    # Entries cached for another top_k are recomputed
+    assert model.recommend('user_1', top_k=1) == expected[:1]
+    model.fit([('user_1', 'item_4', current_unixtime, 5.0)])
+    assert "item_4" not in model.recommend('user_1', top_k=1)
+
+def test_recommend_min_popularity(model):
+    model.add_interactions([('user_1', 'item_a', 1.0, 5.0),
+                            ('user_2', 'item_b', 1.0, 1.0),
+                            ('user_3', 'item_b', 1.0, 1.0),
+                            ('user_3', 'item_c', 1.0, 1.0)])
+    # item_a -> item_b: 0.2, item_a -> item_c: 0.8
+    model.import_similarity_matrix(["item_a", "item_b", "item_c"], indptr=[0, 2, 2, 2], indices=[1, 2], data=[0.2, 0.8])
+
+    assert model.recommend('user_1', top_k=2) == ["item_c", "item_b"]
+    assert model.recommend('user_1', top_k=2, min_popularity=2) == ["item_b"]
+    assert model.recommend('user_1', top_k=2, candidate_items=["item_c"], min_popularity=2) == []
+
+def test_recommend_min_popularity_filters_interacted(model):
+    model.add_interactions([('user_1', 'item_a', 1.0, 5.0),
+                            ('user_2', 'item_a', 1.0, 1.0),
+                            ('user_2', 'item_b', 1.0, 1.0),
+                            ('user_3', 'item_b', 1.0, 1.0),
+                            ('user_3', 'item_c', 1.0, 1.0)])
+    # item_a -> item_b: 0.2, item_a -> item_c: 0.8
+    model.import_similarity_matrix(["item_a", "item_b", "item_c"], indptr=[0, 2, 2, 2], indices=[1, 2], data=[0.2, 0.8])
+
+    # item_a is popular but already interacted by user_1
+    assert model.recommend('user_1', top_k=2, min_popularity=2) == ["item_b"]
+    assert set(model.recommend('user_1', top_k=2, min_popularity=2, filter_interacted=False)) == {"item_a", "item_b"}
+
+def test_record_impressions():
+    model = SLIM(impression_decay=1.0)
+    model.add_interactions([('user_1', 'item_a', 1.0, 2.0),
+                            ('user_2', 'item_b', 1.0, 1.0),
+                            ('user_2', 'item_c', 1.0, 1.0),
+                            ('user_2', 'item_d', 1.0, 1.0)])
+    # item_a -> item_b: 0.5, item_a -> item_c: 1.0, item_a -> item_d: 1.5
+    model.import_similarity_matrix(["item_a", "item_b", "item_c", "item_d"], indptr=[0, 3, 3, 3, 3], indices=[1, 2, 3], data=[0.5, 1.0, 1.5])
+    assert model.recommend('user_1', top_k=3) == ["item_d", "item_c", "item_b"]
+
+    # A fresh impression zeroes the score, and the penalty halves every day
+    # Impressions decay relative to the latest interaction time rather than the wall clock
+    now = model.interactions.max_timestamp
+    model.record_impressions('user_1', ["item_d"])
+    model.record_impressions('user_1', ["item_c"], tstamp=now - 43200.0)
+    assert model.impressions == {0: {3: now, 2: now - 43200.0}}
+    assert model.recommend('user_1', top_k=3) == ["item_b", "item_c", "item_d"]
+    assert model.recommend('user_1', candidate_items=["item_b", "item_d"], top_k=2) == ["item_b", "item_d"]
+
+    # Impressions are ignored without impression_decay
+    model.impression_decay = None
+    assert model.recommend('user_1', top_k=3) == ["item_d", "item_c", "item_b"]
+
+def test_record_impressions_pruning():
+    model = SLIM(impression_decay=1.0)
+    model.add_interactions([('user_1', 'item_a', 1.0, 2.0),
+                            ('user_2', 'item_b', 1.0, 1.0)])
+    now = model.interactions.max_timestamp
+    # Impressions of unknown users are ignored
+    model.record_impressions('user_3', ["item_a"], tstamp=now)
+    assert model.user_ids.get_id('user_3') is None
+    assert model.impressions == {}
+
+    # The penalty of a 30-day-old impression is below MIN_IMPRESSION_PENALTY
+    model.record_impressions('user_1', ["item_b"], tstamp=now - 30 * 86400.0)
+    assert model.impressions == {}
+    model.record_impressions('user_2', ["item_a"], tstamp=now)
+    assert model.prune_impressions() == 0
+    model.impressions[0] = {1: now - 30 * 86400.0}
+    assert model.prune_impressions() == 1
+    assert model.impressions == {1: {0: now}}
+    # Computing the penalties on recommend does not prune, and prune_impressions decays to a given time
+    model.impressions[0] = {1: now - 30 * 86400.0}
+    assert model._impression_penalties(0) == {}
+    assert model.impressions == {0: {1: now - 30 * 86400.0}, 1: {0: now}}
+    assert model.prune_impressions(now=now + 30 * 86400.0) == 2
+    assert model.impressions == {}
+
+def test_record_impressions_pass_through():
+    model = SLIM(impression_decay=1.0)
+    model.add_interactions([(0, 0, 1.0, 2.0),
+                            (1, 1, 1.0, 1.0),
+                            (1, 2, 1.0, 1.0),
+                            (1, 3, 1.0, 1.0)])
+    # 0 -> 1: 0.5, 0 -> 2: 1.0, 0 -> 3: 1.5
+    model.import_similarity_matrix([0, 1, 2, 3], indptr=[0, 3, 3, 3, 3], indices=[1, 2, 3], data=[0.5, 1.0, 1.5])
+    assert model.recommend(0, top_k=3) == [3, 2, 1]
+
+    model.record_impressions(0, [3])
+    assert model.recommend(0, top_k=3) == [2, 1, 3]
+
+def test_block_item(model):
+    model.add_interactions([('user_1', 'item_a', 1.0, 2.0),
+                            ('user_2', 'item_b', 1.0, 1.0),
+                            ('user_2', 'item_c', 1.0, 1.0),
+                            ('user_2', 'item_d', 1.0, 1.0)])
+    # item_a -> item_b: 0.5, item_a -> item_c: 1.0, item_a -> item_d: 1.5
+    model.import_similarity_matrix(["item_a", "item_b", "item_c", "item_d"], indptr=[0, 3, 3, 3, 3], indices=[1, 2, 3], data=[0.5, 1.0, 1.5])
+
+    model.block_item("item_d")
+    assert model.recommend('user_1', top_k=2) == ["item_c", "item_b"]
+    assert model.recommend_batch(['user_1'], top_k=2) == [["item_c", "item_b"]]
+    assert model.similar_items('item_d', top_k=2) == ["item_a"]
+    model.block_item("item_a")
+    assert model.similar_items('item_d', top_k=2) == []
+
+    model.unblock_item("item_a")
+    model.unblock_item("item_d")
+    assert model.recommend('user_1', top_k=2) == ["item_d", "item_c"]
+
+def test_block_item_all_recommendations(model, tmp_path):
+    import pandas as pd
+    model.add_interactions([('user_1', 'item_a', 1.0, 2.0),
+                            ('user_2', 'item_b', 1.0, 1.0),
+                            ('user_2', 'item_c', 1.0, 1.0),
+                            ('user_2', 'item_d', 1.0, 1.0)])
+    # item_a -> item_b: 0.5, item_a -> item_c: 1.0, item_a -> item_d: 1.5
+    model.import_similarity_matrix(["item_a", "item_b", "item_c", "item_d"], indptr=[0, 3, 3, 3, 3], indices=[1, 2, 3], data=[0.5, 1.0, 1.5])
+    model.block_item("item_d")
+
+    assert model.recommend_above('user_1', min_score=0.0) == [("item_c", 2.0), ("item_b", 1.0)]
+    assert model.recommend_with_reason('user_1', top_k=2) == [("item_c", 2.0, "item_a"), ("item_b", 1.0, "item_a")]
+    assert model.recommend_grouped('user_1', per_source_k=2) == [("item_a", [("item_c", 1.0), ("item_b", 0.5)])]
+
+    path = tmp_path / "recommendations.csv"
+    assert model.export_recommendations(str(path), top_k=5) == 3
+    df = pd.read_csv(path)
+    assert df.values.tolist() == [["user_1", 1, "item_c", 2.0], ["user_1", 2, "item_b", 1.0], ["user_2", 1, "item_a", 0.0]]
+
+def test_item_namespace(model):
+    from scipy.sparse import csc_matrix
+    model.item_ids.set_namespace("tenant_a")
+    model.add_interactions([('user_1', 'item_x', 1.0, 2.0),
+                            ('user_2', 'item_y', 1.0, 1.0)])
+    model.item_ids.set_namespace("tenant_b")
+    model.add_interactions([('user_3', 'item_x', 1.0, 1.0),
+                            ('user_3', 'item_z', 1.0, 1.0)])
+    # item_x of tenant_a -> item_y: 0.5, item_x of tenant_b: 1.0, item_z: 1.5
+    model.model.item_similarity = csc_matrix(([0.5, 1.0, 1.5], ([0, 0, 0], [1, 2, 3])), shape=(4, 4))
+
+    model.item_ids.set_namespace("tenant_a")
+    assert model.recommend('user_1', top_k=3) == ["item_y"]
+    assert model.recommend_above('user_1', min_score=0.0) == [("item_y", 1.0)]
+    model.item_ids.set_namespace("tenant_b")
+    assert model.recommend('user_1', top_k=3) == ["item_z", "item_x"]
+    model.item_ids.set_namespace(None)
+    assert model.recommend('user_1', top_k=3) == ["item_z", "item_x", "item_y"]
+
+    model.item_ids.set_namespace("tenant_a")
+    assert [item for item, _ in model.trending_items(top_k=3)] == ["item_x", "item_y"]
+    # The predicate only sees items of the current namespace
+    seen = []
+    assert model.recommend_filtered('user_1', lambda item: seen.append(item) is None, top_k=3) == ["item_y"]
+    assert seen == ["item_y"]
+
+def test_recommend_min_profile_signal():
+    import time
+    current_unixtime = time.time()
+    interactions = [('user_1', 'item_1', current_unixtime, 5.0),
+                   ('user_1', 'item_3', current_unixtime, 4.0),
+                   ('user_2', 'item_1', current_unixtime, 3.0),
+                   ('user_2', 'item_4', current_unixtime, 3.0),
+                   ('user_3', 'item_4', current_unixtime, 2.0),
+                   ('user_3', 'item_4', current_unixtime, -2.0)]
+    model = SLIM(min_profile_signal=0.0)
+    model.fit(interactions)
+
+    # user_3 only has a zero rating, so popular items are recommended instead
+    assert model.recommend('user_3', top_k=2) == ["item_1", "item_3"]
+
+def test_fit_min_item_interactions():
+    import time
+    current_unixtime = time.time()
+    interactions = [('user_1', 'item_1', current_unixtime, 5.0),
+                   ('user_1', 'item_3', current_unixtime, 4.0),
+                   ('user_2', 'item_1', current_unixtime, 3.0),
+                   ('user_2', 'item_3', current_unixtime, 3.0),
+                   ('user_2', 'item_2', current_unixtime, 3.0)]
+    model = SLIM(min_item_interactions=2)
+    model.fit(interactions)
+    # item_2 has a single interaction, so no similarity targeting it is learned
+    item_2 = model.item_ids.get_id('item_2')
+    assert model.model.item_similarity[:, item_2].nnz == 0
+    assert model.similar_items('item_2', top_k=5) == []
+
+    # item_2 is fitted once it gains enough support
+    model.fit([('user_1', 'item_2', current_unixtime, 3.0),
+               ('user_1', 'item_1', current_unixtime, 5.0),
+               ('user_2', 'item_1', current_unixtime, 3.0)], update_interaction=True)
+    assert model.model.item_similarity[:, item_2].nnz > 0
+
+def test_fit_freeze_structure(model):
+    import time
+    current_unixtime = time.time()
+    interactions = [('user_1', 'item_1', current_unixtime, 5.0),
+                   ('user_1', 'item_3', current_unixtime, 4.0),
+                   ('user_2', 'item_1', current_unixtime, 3.0),
+                   ('user_2', 'item_3', current_unixtime, 3.0)]
+    model.fit(interactions)
+    nnz = model.model.item_similarity.nnz
+
+    model.fit([('user_1', 'item_4', current_unixtime, 5.0),
+               ('user_2', 'item_4', current_unixtime, 3.0),
+               ('user_3', 'item_1', current_unixtime, 4.0),
+               ('user_3', 'item_4', current_unixtime, 4.0)], freeze_structure=True)
+    # No new item pairs are introduced
+    assert model.model.item_similarity.nnz <= nnz
+    assert model.similar_items('item_4', top_k=5) == []
+
+def test_import_similarity_matrix(model):
+    import time
+    current_unixtime = time.time()
+    model.add_interactions([('user_1', 'item_a', current_unixtime, 5.0),
+                            ('user_2', 'item_b', current_unixtime, 3.0)])
+    # item_a -> item_b: 0.5, item_a -> item_c: 0.8, item_b -> item_a: 0.3
+    model.import_similarity_matrix(["item_a", "item_b", "item_c"], indptr=[0, 2, 3, 3], indices=[1, 2, 0], data=[0.5, 0.8, 0.3])
+
+    assert model.similar_items('item_a', top_k=5, ret_scores=True) == [("item_b", pytest.approx(0.3))]
+    assert model.recommend('user_1', top_k=2) == ["item_c", "item_b"]
+
+def test_import_similarity_matrix_strips_diagonal(model):
+    # item_a -> item_a: 1.0, item_a -> item_b: 0.5, item_b -> item_b: 1.0
+    model.import_similarity_matrix(["item_a", "item_b"], indptr=[0, 2, 3], indices=[0, 1, 1], data=[1.0, 0.5, 1.0])
+
+    assert model.model.item_similarity.nnz == 1
+    assert model.model.item_similarity.diagonal().sum() == 0.0
+    assert model.similar_items('item_b', top_k=5, ret_scores=True) == [("item_a", pytest.approx(0.5))]
+
+def test_fit_validated(model):
+    import time
+    current_unixtime = time.time()
+    # Every user has 3 items and every item has at least 2 users
+    interactions = [(f'user_{u}', f'item_{(u + i) % 5}', current_unixtime, 5.0) for u in range(4) for i in range(3)]
+    # A single interaction is held out, whose user and item remain known from the other interactions.
+    # As top_k covers all 5 items, every item the user has not interacted with in training is recommended.
+    recall = model.fit_validated(interactions, validation_fraction=0.1, top_k=5, random_seed=42)
+    assert recall == 1.0
+    # Held-out interactions are not added to the user profiles
+    assert sum(model.interactions.get_item_counts().values()) == len(interactions) - 1
+
+    # Users whose interactions are all held out have nothing recommended
+    model = SLIM()
+    interactions = [(f'user_{u}', f'item_{u}', current_unixtime, 5.0) for u in range(4)]
+    assert model.fit_validated(interactions, validation_fraction=0.5, top_k=5, random_seed=42) == 0.0
+
+    with pytest.raises(AssertionError):
+        model.fit_validated(interactions, validation_fraction=1.0)
+
+def test_export_interactions_csr(model):
+    model.add_interactions([('user_1', 'item_b', 1.0, 2.0),
+                            ('user_2', 'item_a', 2.0, 3.0),
+                            ('user_1', 'item_a', 3.0, 4.0)])
+    indptr, indices, data, users, items = model.export_interactions_csr()
+
+    assert users == ['user_1', 'user_2']
+    assert items == ['item_b', 'item_a']
+    assert indptr.tolist() == [0, 2, 3]
+    assert indices.tolist() == [0, 1, 1]
+    assert data.tolist() == [2.0, 4.0, 3.0]
+
+def test_export_interactions_csr_empty(model):
+    indptr, indices, data, users, items = model.export_interactions_csr()
+
+    assert users == []
+    assert items == []
+    assert indptr.tolist() == [0]
+    assert indices.tolist() == []
+    assert data.tolist() == []
+
+def test_similar_users(model):
+    model.add_interactions([('user_1', 'item_a', 1.0, 1.0),
+                            ('user_1', 'item_b', 1.0, 1.0),
+                            ('user_2', 'item_a', 1.0, 1.0),
+                            ('user_2', 'item_b', 1.0, 1.0),
+                            ('user_3', 'item_a', 1.0, 1.0),
+                            ('user_3', 'item_c', 1.0, 1.0),
+                            ('user_4', 'item_d', 1.0, 1.0)])
+
+    results = model.similar_users(['user_1', 'unknown_user', 'user_4'], top_k=5)
+    assert results[0] == [('user_2', pytest.approx(1.0)), ('user_3', pytest.approx(0.5))]
+    assert results[1] == []
+    assert results[2] == []
+    assert model.similar_users(['user_1'], top_k=1) == [[('user_2', pytest.approx(1.0))]]
+
+def test_validate(model):
+    import numpy as np
+    model.add_interactions([('user_1', 'item_a', 1.0, 5.0),
+                            ('user_2', 'item_b', 1.0, 3.0)])
+    assert model.validate() == []
+    model.import_similarity_matrix(["item_a", "item_b"], indptr=[0, 1, 2], indices=[1, 0], data=[0.5, 0.25])
+    assert model.validate() == []
+
+    model.model.item_similarity[0, 0] = 1.0
+    model.model.item_similarity[0, 1] = np.nan
+    violations = model.validate()
+    assert len(violations) == 2
+    assert "non-finite" in violations[0]
+    assert "self-loop" in violations[1]
+
+def test_sharded_fit():
+    import numpy as np
+    import time
+    current_unixtime = time.time()
+    interactions = [('user_1', 'item_1', current_unixtime, 5.0),
+                   ('user_1', 'item_3', current_unixtime, 4.0),
+                   ('user_1', 'item_4', current_unixtime, 3.0),
+                   ('user_2', 'item_1', current_unixtime, 3.0),
+                   ('user_2', 'item_2', current_unixtime, 2.0),
+                   ('user_2', 'item_4', current_unixtime, 3.0),
+                   ('user_3', 'item_1', current_unixtime, 4.0),
+                   ('user_3', 'item_3', current_unixtime, 2.0),
+                   ('user_3', 'item_4', current_unixtime, 4.0)]
+    full_model = SLIM()
+    full_model.add_interactions(interactions)
+    full_model.bulk_fit()
+
+    shard_weights = []
+    for shard_index in range(2):
+        shard_model = SLIM(shard=(shard_index, 2))
+        shard_model.add_interactions(interactions)
+        shard_model.bulk_fit()
+        weights = shard_model.model.item_similarity.toarray()
+        # Only the target items of the shard are fitted
+        assert not weights[:, [j for j in range(weights.shape[1]) if j % 2 != shard_index]].any()
+        shard_weights.append(weights)
+
+    assert np.allclose(sum(shard_weights), full_model.model.item_similarity.toarray())
+
+    with pytest.raises(AssertionError):
+        SLIM(shard=(2, 2))
+
+def test_strict():
+    model = SLIM(strict=True)
+    model.add_interactions([('user_1', 'item_a', 1.0, 5.0),
+                            ('user_2', 'item_b', 1.0, 3.0)])
+    model.import_similarity_matrix(["item_a", "item_b"], indptr=[0, 1, 2], indices=[1, 0], data=[0.5, 0.25])
+
+    assert model.recommend('user_1', top_k=1) == ["item_b"]
+    with pytest.raises(KeyError, match="unknown_user"):
+        model.recommend('unknown_user')
+    with pytest.raises(KeyError, match="unknown_item"):
+        model.similar_items('unknown_item')
+    with pytest.raises(KeyError, match="unknown_item"):
+        model.auc([('user_1', 'unknown_item')], [('user_1', 'item_b')])
+    with pytest.raises(KeyError, match="unknown_user"):
+        model.recommend('unknown_user', min_popularity=100)
+    with pytest.raises(KeyError, match="unknown_user"):
+        model.recommend_batch(['user_1', 'unknown_user'])
+    with pytest.raises(KeyError, match="unknown_user"):
+        model.recommend_above('unknown_user', min_score=0.0)
+    with pytest.raises(KeyError, match="unknown_user"):
+        model.recommend_with_reason('unknown_user')
+    with pytest.raises(KeyError, match="unknown_user"):
+        model.user_affinity('user_1', 'unknown_user')
+    with pytest.raises(KeyError, match="unknown_user"):
+        model.batch_explain(['unknown_user'], 'item_b')
+    # The unknown item is not registered by the failed lookup
+    assert model.item_ids.get_id('unknown_item') is None
+
+def test_reset_weights(model):
+    import time
+    current_unixtime = time.time()
+    interactions = [('user_1', 'item_1', current_unixtime, 5.0),
+                   ('user_2', 'item_2', current_unixtime, -2.0),
+                   ('user_2', 'item_1', current_unixtime, 3.0),
+                   ('user_2', 'item_4', current_unixtime, 3.0),
+                   ('user_1', 'item_3', current_unixtime, 4.0)]
+    model.fit(interactions)
+    expected = model.recommend('user_1', top_k=5)
+
+    model.reset_weights()
+    assert model.model.item_similarity is None
+    assert model.interactions.get_user_item_rating(model.user_ids.get_id('user_1'), model.item_ids.get_id('item_1')) == 5.0
+
+    model.bulk_fit(progress_bar=False)
+    assert model.recommend('user_1', top_k=5) == expected
+
+def test_from_parts(model):
+    import time
+    current_unixtime = time.time()
+    interactions = [('user_1', 'item_1', current_unixtime, 5.0),
+                   ('user_2', 'item_1', current_unixtime, 3.0),
+                   ('user_2', 'item_4', current_unixtime, 3.0),
+                   ('user_1', 'item_3', current_unixtime, 4.0)]
+    model.fit(interactions)
+
+    assembled = SLIM.from_parts(model.interactions, model.model.item_similarity, model.user_ids, model.item_ids)
+    assert assembled.recommend('user_1', top_k=5) == model.recommend('user_1', top_k=5)
+    assert assembled.similar_items('item_1', top_k=5) == model.similar_items('item_1', top_k=5)
+
+    # Inconsistent shapes are rejected
+    with pytest.raises(ValueError):
+        SLIM.from_parts(model.interactions, model.model.item_similarity[:2, :2], model.user_ids, model.item_ids)
+
+def test_similar_items_missing_similarity():
+    import time
+    current_unixtime = time.time()
+    interactions = [('user_1', 'item_1', current_unixtime, 5.0),
+                   ('user_1', 'item_3', current_unixtime, 4.0),
+                   ('user_1', 'item_4', current_unixtime, 3.0),
+                   ('user_2', 'item_1', current_unixtime, 3.0),
+                   ('user_2', 'item_2', current_unixtime, -2.0),
+                   ('user_2', 'item_4', current_unixtime, 3.0),
+                   ('user_3', 'item_1', current_unixtime, 4.0),
+                   ('user_3', 'item_3', current_unixtime, 2.0),
+                   ('user_3', 'item_4', current_unixtime, 4.0)]
+
+    model = SLIM(missing_similarity="zero")
+    model.fit(interactions)
+    results = model.similar_items('item_1', top_k=5, ret_scores=True)
+    assert [item for item, _ in results] == ["item_4", "item_3", "item_2"]
+    assert results[-1][1] == 0.0
+
+    model = SLIM(missing_similarity="cooccurrence")
+    model.fit(interactions)
+    results = model.similar_items('item_1', top_k=5, ret_scores=True)
+    assert [item for item, _ in results] == ["item_4", "item_3", "item_2"]
+    assert results[-1][1] == pytest.approx(1.0 / 3.0)
+
+def test_similar_items_missing_similarity_below_negative():
+    model = SLIM(missing_similarity="zero")
+    model.add_interactions([('user_1', 'item_a', 1.0, 1.0),
+                            ('user_1', 'item_b', 1.0, 1.0),
+                            ('user_2', 'item_c', 1.0, 1.0),
+                            ('user_2', 'item_d', 1.0, 1.0)])
+    # item_b -> item_a: 0.5, item_c -> item_a: -0.5
+    model.import_similarity_matrix(["item_a", "item_b", "item_c", "item_d"], indptr=[0, 0, 1, 2, 2], indices=[0, 0], data=[0.5, -0.5])
+
+    # item_d without a learned similarity ranks above the negatively similar item_c
+    assert model.similar_items('item_a', top_k=3, ret_scores=True) == [("item_b", 0.5), ("item_d", 0.0), ("item_c", -0.5)]
+    assert model.similar_items('item_a', top_k=2) == ["item_b", "item_d"]
+
+def test_novelty_and_diversity(model):
+    import math
+    model.add_interactions([('user_1', 'item_a', 1.0, 1.0),
+                            ('user_2', 'item_a', 1.0, 1.0),
+                            ('user_2', 'item_b', 1.0, 1.0),
+                            ('user_3', 'item_c', 1.0, 1.0),
+                            ('user_3', 'item_d', 1.0, 1.0),
+                            ('user_4', 'item_c', 1.0, 1.0)])
+    # item_a -> item_b: 1.0, item_a -> item_c: 0.5, item_d -> item_c: 0.5
+    model.import_similarity_matrix(["item_a", "item_b", "item_c", "item_d"], indptr=[0, 2, 2, 2, 3], indices=[1, 2, 2], data=[1.0, 0.5, 0.5])
+
+    assert model.recommend('user_1', top_k=2) == ["item_b", "item_c"]
+    # item_b is interacted by 1 of 4 users and item_c by 2 of 4 users: (log2(4) + log2(2)) / 2
+    assert model.novelty('user_1', top_k=2) == pytest.approx(1.5)
+    # Columns of item_b (1.0, 0, 0, 0) and item_c (0.5, 0, 0, 0.5) have cosine similarity 1 / sqrt(2)
+    assert model.recommendation_diversity('user_1', top_k=2) == pytest.approx(1.0 - 1.0 / math.sqrt(2.0), rel=1e-6)
+
+    # Unknown users have no recommendations to measure
+    assert model.novelty('unknown_user', top_k=5) == 0.0
+    assert model.recommendation_diversity('unknown_user', top_k=5) == 0.0
+
+    # Blocked items are measured as recommend() would return them
+    model.block_item("item_b")
+    assert model.recommend('user_1', top_k=1) == ["item_c"]
+    assert model.novelty('user_1', top_k=1) == pytest.approx(1.0)
+
+def test_trending_items_skips_blocked(model):
+    model.add_interactions([('user_1', 'item_a', 1.0, 1.0),
+                            ('user_2', 'item_a', 1.0, 1.0),
+                            ('user_2', 'item_b', 1.0, 1.0),
+                            ('user_3', 'item_c', 1.0, 1.0),
+                            ('user_4', 'item_c', 1.0, 1.0)])
+    assert [item for item, _ in model.trending_items(top_k=2)] == ["item_a", "item_c"]
+
+    model.block_item("item_a")
+    assert [item for item, _ in model.trending_items(top_k=2)] == ["item_c", "item_b"]
+
 if __name__ == "__main__":
     pytest.main()
@@ -0,0 +1,93 @@
+import pytest
+
+from rtrec.utils.identifiers import Identifier, IdentifierError, VocabularyFullError
+
+def test_identify_first_seen_order():
+    identifier = Identifier()
+    values = ["c", "a", "b", "a", "c", "d"]
+    ids = [identifier.identify(value) for value in values]
+    assert ids == [0, 1, 2, 1, 0, 3]
+    assert [identifier.get(i) for i in range(4)] == ["c", "a", "b", "d"]
+
+def test_identify_is_reproducible():
+    values = [("user", i % 7) for i in range(100)] + ["x", 3.5, "y"]
+    first, second = Identifier(), Identifier()
+    assert [first.identify(v) for v in values] == [second.identify(v) for v in values]
+    assert first.id_to_obj == second.id_to_obj
+
+def test_get_unknown_id():
+    identifier = Identifier()
+    identifier.identify("a")
+    assert identifier.get_id("b") is None
+    with pytest.raises(IdentifierError):
+        identifier.get(1)
+
+def test_namespace():
+    identifier = Identifier()
+    identifier.set_namespace("tenant_a")
+    id_a = identifier.identify("item42")
+    identifier.set_namespace("tenant_b")
+    id_b = identifier.identify("item42")
+    assert id_a != id_b
+    assert identifier.get_id("item42") == id_b
+    assert identifier.get(id_a) == identifier.get(id_b) == "item42"
+
+    identifier.set_namespace("tenant_a")
+    assert identifier.get_id("item42") == id_a
+    # Integers are identified within the namespace instead of passing through
+    assert identifier.identify(42) == 2
+    assert identifier.pass_through is False
+
+def test_namespace_does_not_collide_with_tuples():
+    identifier = Identifier()
+    id_tuple = identifier.identify(("tenant_a", "item42"))
+    identifier.set_namespace("tenant_a")
+    id_a = identifier.identify("item42")
+    assert id_a != id_tuple
+    assert identifier.get(id_a) == "item42"
+    assert identifier.get(id_tuple) == ("tenant_a", "item42")
+
+def test_in_namespace():
+    identifier = Identifier()
+    id_bare = identifier.identify("item1")
+    identifier.set_namespace("tenant_a")
+    id_a = identifier.identify("item1")
+    identifier.set_namespace("tenant_b")
+    id_b = identifier.identify("item1")
+    assert [identifier.in_namespace(i) for i in (id_bare, id_a, id_b)] == [False, False, True]
+    assert identifier.num_outside_namespace() == 2
+
+    identifier.set_namespace(None)
+    assert [identifier.in_namespace(i) for i in (id_bare, id_a, id_b)] == [True, True, True]
+    assert identifier.num_outside_namespace() == 0
+
+def test_namespace_with_pass_through():
+    identifier = Identifier()
+    identifier.identify(1)
+    with pytest.raises(ValueError):
+        identifier.set_namespace("tenant_a")
+
+def test_max_vocab():
+    identifier = Identifier(max_vocab=2)
+    assert identifier.identify("a") == 0
+    assert identifier.identify("b") == 1
+    with pytest.raises(VocabularyFullError):
+        identifier.identify("c")
+    # Known objects are still identified
+    assert identifier.identify("a") == 0
+    assert identifier.get_id("c") is None
+    assert identifier.num_rejected == 1
+
+def test_check_capacity():
+    identifier = Identifier(max_vocab=1)
+    identifier.check_capacity("a")
+    assert identifier.identify("a") == 0
+    # Known objects pass the check
+    identifier.check_capacity("a")
+    with pytest.raises(VocabularyFullError):
+        identifier.check_capacity("b")
+    assert identifier.get_id("b") is None
+    assert identifier.num_rejected == 1
+
+if __name__ == "__main__":
+    pytest.main()
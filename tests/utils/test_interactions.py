@@ -1,8 +1,9 @@
 import pytest
 from collections import Counter
+import math
 import time
 from time import sleep
-from rtrec.utils.interactions import UserItemInteractions
+from rtrec.utils.interactions import RatingTransform, UserItemInteractions
 from scipy.sparse import csc_matrix, coo_matrix
 
 @pytest.fixture
@@ -113,6 +114,89 @@ def test_decayed_rating_after_7days(interactions_with_decay):
     # Check if the decayed rating is as expected, allowing some tolerance for floating-point comparison
     assert abs(decayed_rating - expected_rating) < 0.001
 
+def test_rating_transform():
+    interactions = UserItemInteractions(min_value=-5, max_value=10, rating_transform="log1p")
+    assert interactions.rating_transform is RatingTransform.LOG1P
+    tstamp = time.time()  # Use float timestamp
+    interactions.add_interaction(1, 10, tstamp, 3.0)
+    interactions.add_interaction(1, 10, tstamp, 4.0)
+    interactions.add_interaction(1, 20, tstamp, -3.0)
+    # The transformation applies to the accumulated rating
+    assert interactions.get_user_item_rating(1, 10) == pytest.approx(math.log1p(7.0))
+    assert interactions.get_user_item_rating(1, 20) == pytest.approx(-math.log1p(3.0))
+    assert interactions.to_csr()[1, 10] == pytest.approx(math.log1p(7.0))
+
+    interactions = UserItemInteractions(min_value=-5, max_value=10, rating_transform=RatingTransform.SQRT)
+    interactions.add_interaction(1, 10, tstamp, 9.0)
+    assert interactions.get_user_item_rating(1, 10) == pytest.approx(3.0)
+
+def test_get_item_users(interactions):
+    tstamp = time.time()  # Use float timestamp
+    interactions.add_interaction(1, 10, tstamp, 3.0)
+    interactions.add_interaction(2, 10, tstamp, 5.0)
+    interactions.add_interaction(2, 20, tstamp, 1.0)
+    assert interactions.get_item_users(10) == [(2, 5.0), (1, 3.0)]
+    assert interactions.get_item_users(30) == []
+
+def test_get_item_counts(interactions):
+    tstamp = time.time()  # Use float timestamp
+    interactions.add_interaction(1, 10, tstamp, 5.0)
+    interactions.add_interaction(1, 20, tstamp, 3.0)
+    interactions.add_interaction(2, 10, tstamp, 3.0)
+    # Repeated interactions of the same user are counted once
+    interactions.add_interaction(2, 10, tstamp, 1.0)
+    interactions.add_interaction(1, 20, tstamp, 2.0, upsert=True)
+    assert interactions.get_item_counts() == {10: 2, 20: 1}
+    assert interactions.get_item_counts([20, 30]) == {20: 1, 30: 0}
+
+def test_get_co_occurrences(interactions):
+    tstamp = time.time()  # Use float timestamp
+    interactions.add_interaction(1, 10, tstamp, 5.0)
+    interactions.add_interaction(1, 20, tstamp, 3.0)
+    interactions.add_interaction(2, 10, tstamp, 3.0)
+    interactions.add_interaction(2, 30, tstamp, 3.0)
+    interactions.add_interaction(3, 20, tstamp, 3.0)
+    assert interactions.get_co_occurrences(10) == {20: 0.5, 30: 0.5}
+    assert interactions.get_co_occurrences(30) == {10: 1.0}
+    assert interactions.get_co_occurrences(99) == {}
+
+def test_get_trending_items(interactions):
+    tstamp = time.time()  # Use float timestamp
+    interactions.add_interaction(1, 10, tstamp, 5.0)
+    interactions.add_interaction(2, 10, tstamp - 2 * 86400, 3.0)
+    interactions.add_interaction(3, 20, tstamp, 1.0)
+    interactions.add_interaction(4, 30, tstamp, -1.0)
+
+    trending = interactions.get_trending_items(5, half_life_days=2)
+    assert [item_id for item_id, _ in trending] == [10, 20]
+    assert trending[0][1] == pytest.approx(1.5, rel=1e-3)
+    assert trending[1][1] == pytest.approx(1.0, rel=1e-3)
+    # Without decay, all positive interactions count equally
+    assert interactions.get_trending_items(1) == [(10, 2.0)]
+
+def test_temporal_split(interactions):
+    interactions.add_interaction(0, 0, tstamp=100, delta=5)
+    interactions.add_interaction(0, 1, tstamp=200, delta=3)
+    interactions.add_interaction(1, 1, tstamp=150, delta=4)
+    interactions.add_interaction(1, 2, tstamp=250, delta=2)
+    interactions.add_interaction(2, 0, tstamp=300, delta=1)
+
+    matrix, held_out = interactions.temporal_split(cutoff=200)
+    expected = csc_matrix(([5, 4], ([0, 1], [0, 1])), shape=(3, 3))
+    assert (matrix != expected).nnz == 0
+    assert held_out == {0: [1], 1: [2], 2: [0]}
+
+def test_temporal_split_reinteraction(interactions):
+    interactions.add_interaction(0, 0, tstamp=100, delta=5)
+    interactions.add_interaction(0, 1, tstamp=150, delta=3)
+    # A re-interaction after the cutoff moves the whole pair to the held-out items
+    interactions.add_interaction(0, 0, tstamp=250, delta=2)
+
+    matrix, held_out = interactions.temporal_split(cutoff=200)
+    expected = csc_matrix(([3], ([0], [1])), shape=(1, 2))
+    assert (matrix != expected).nnz == 0
+    assert held_out == {0: [0]}
+
 def test_to_csc():
     interactions = UserItemInteractions(min_value=-5, max_value=10, decay_in_days=None)
     interactions.add_interaction(0, 0, tstamp=12345, delta=5)
@@ -1,6 +1,6 @@
 from typing import List
 import pytest
-from rtrec.utils.lru import LRUFreqSet
+from rtrec.utils.lru import LRUCache, LRUFreqSet
 
 @pytest.fixture
 def lru_set():
@@ -149,3 +149,31 @@ def test_get_freq_items_no_exclusions(lru_set_with_entries):
     result = list(lru_set.get_freq_items(n=4, exclude_items=[]))
     expected = ["A", "B", "C", "D"]  # Top 4 with no exclusions
     assert result == expected, f"Expected {expected}, got {result}"
+
+def test_lru_cache_get_and_put():
+    cache = LRUCache(capacity=2)
+    cache.put("a", 1)
+    cache.put("b", 2)
+    assert cache.get("a") == 1
+    assert cache.get("c") is None
+    assert cache.get("c", default=0) == 0
+
+    # "b" is the least recently used entry and gets evicted
+    cache.put("c", 3)
+    assert "b" not in cache
+    assert "a" in cache and "c" in cache
+    assert len(cache) == 2
+
+def test_lru_cache_invalidate_and_clear():
+    cache = LRUCache(capacity=3)
+    cache.put("a", 1)
+    cache.put("b", 2)
+    cache.invalidate("a")
+    cache.invalidate("unknown")
+    assert "a" not in cache
+    cache.clear()
+    assert len(cache) == 0
+
+def test_lru_cache_invalid_capacity():
+    with pytest.raises(ValueError):
+        LRUCache(capacity=0)
@@ -9,6 +9,7 @@ from rtrec.utils.metrics import (
     reciprocal_rank,
     average_precision,
     auc,
+    score_auc,
     true_positives,
 )
 
@@ -156,5 +157,15 @@ def test_true_positives(ranked_list, ground_truth, recommend_size, expected):
     """Test the true_positives function with various cases."""
     assert true_positives(ranked_list, ground_truth, recommend_size) == expected
 
+@pytest.mark.parametrize("positive_scores, negative_scores, expected", [
+    ([0.9, 0.8], [0.1, 0.2], 1.0), # perfect separation
+    ([0.1, 0.2], [0.9, 0.8], 0.0), # reversed
+    ([0.5, 0.5], [0.5, 0.5], 0.5), # all tied
+    ([0.9, 0.3], [0.5, 0.1], 0.75),
+    ([], [0.5], 0.0), # no positives
+])
+def test_score_auc(positive_scores, negative_scores, expected):
+    assert score_auc(positive_scores, negative_scores) == pytest.approx(expected)
+
 if __name__ == "__main__":
     pytest.main()